@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+use crate::{states::Collection,Errors};
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        constraint = collection_details.pending_admin == Some(new_admin.key())
+        @ Errors::NotPendingAdmin
+    )]
+    pub collection_details: Account<'info, Collection>,
+
+    pub new_admin: Signer<'info>,
+}
+
+pub fn accept_admin_handler(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let collection_details = &mut ctx.accounts.collection_details;
+
+    collection_details.admin = ctx.accounts.new_admin.key();
+    collection_details.pending_admin = None;
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::MetadataAccount,
+    token_interface::{
+        self,
+        Mint,
+        TokenInterface,
+        SetAuthority,
+        spl_token_2022::instruction::AuthorityType
+    }
+};
+use crate::{states::{Collection,CoReward,Council,CouncilAction,CouncilActionType},Errors};
+
+#[derive(Accounts)]
+pub struct AddCoReward<'info> {
+    #[account(
+        mut,
+        seeds = [b"collection", verified_collection.key().as_ref()],
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin
+    )]
+    pub collection_details: Account<'info, Collection>,
+
+    pub owner: Signer<'info>,
+    
+    #[account(
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
+    )]
+    pub verified_collection: Account<'info, MetadataAccount>,
+
+    #[account(
+        mut,
+        mint::authority = mint_authority,
+        mint::token_program = token_program
+    )]
+    pub token: InterfaceAccount<'info, Mint>,
+
+    /// The mint authority of the token (e.g. the partner), gets it back when the collection is closed
+    pub mint_authority: Signer<'info>,
+
+    /// CHECK: PDA used as the mint authority of the co-reward
+    #[account(
+        seeds = [b"token-authority", collection_details.key().as_ref()],
+        bump = collection_details.token_authority_bump
+    )]
+    pub token_authority: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// The approved AddCoReward action (if the collection is managed by a council)
+    #[account(
+        mut,
+        constraint = Some(council_action.council) == collection_details.council @ Errors::CouncilActionMismatch,
+        constraint = council_action.collection == collection_details.key() @ Errors::CouncilActionMismatch,
+        close = action_creator
+    )]
+    pub council_action: Option<Box<Account<'info, CouncilAction>>>,
+
+    #[account(
+        constraint = Some(council.key()) == collection_details.council @ Errors::NotCouncilMember
+    )]
+    pub council: Option<Box<Account<'info, Council>>>,
+
+    /// CHECK: Validated against the creator stored in the council action
+    #[account(mut)]
+    pub action_creator: Option<AccountInfo<'info>>
+}
+
+impl<'info> AddCoReward<'info> {
+    pub fn set_authority_context(&self) -> CpiContext<'_,'_,'_,'info, SetAuthority<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = SetAuthority {
+            account_or_mint: self.token.to_account_info(),
+            current_authority: self.mint_authority.to_account_info()
+        };
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+/// * The mint authority is handed over to the token-authority PDA, the budget is agreed by both signers
+pub fn add_co_reward_handler(ctx: Context<AddCoReward>, emission: u64, emission_budget: u64) -> Result<()> {
+    CouncilAction::require_approved(
+        &ctx.accounts.collection_details,
+        ctx.accounts.council.as_deref(),
+        ctx.accounts.council_action.as_deref(),
+        ctx.accounts.action_creator.as_ref(),
+        |action| matches!(
+            action,
+            CouncilActionType::AddCoReward { token, emission: approved, emission_budget: approved_budget }
+            if *token == ctx.accounts.token.key() && *approved == emission && *approved_budget == emission_budget
+        )
+    )?;
+
+    let co_reward = CoReward::new(
+        ctx.accounts.token.key(),
+        ctx.accounts.token.decimals,
+        None,
+        ctx.accounts.mint_authority.key(),
+        emission,
+        emission_budget
+    );
+
+    ctx.accounts.collection_details.add_co_reward(co_reward)?;
+
+    let token_authority = ctx.accounts.token_authority.key();
+
+    token_interface::set_authority(
+        ctx.accounts.set_authority_context(),
+        AuthorityType::MintTokens,
+        Some(token_authority)
+    )
+}
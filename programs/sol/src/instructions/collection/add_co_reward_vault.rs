@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::MetadataAccount,
+    token_interface::{
+        Mint,
+        TokenAccount,
+        TokenInterface
+    }
+};
+use crate::{states::{Collection,CoReward,Council,CouncilAction,CouncilActionType},Errors};
+
+#[derive(Accounts)]
+pub struct AddCoRewardVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"collection", verified_collection.key().as_ref()],
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin
+    )]
+    pub collection_details: Account<'info, Collection>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
+    )]
+    pub verified_collection: Account<'info, MetadataAccount>,
+
+    pub token: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA used as the owner of the vault
+    #[account(
+        seeds = [b"token-authority", collection_details.key().as_ref()],
+        bump = collection_details.token_authority_bump
+    )]
+    pub token_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"co-reward-vault", collection_details.key().as_ref(), token.key().as_ref()],
+        bump,
+        token::mint = token,
+        token::authority = token_authority,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// The approved AddCoRewardVault action (if the collection is managed by a council)
+    #[account(
+        mut,
+        constraint = Some(council_action.council) == collection_details.council @ Errors::CouncilActionMismatch,
+        constraint = council_action.collection == collection_details.key() @ Errors::CouncilActionMismatch,
+        close = action_creator
+    )]
+    pub council_action: Option<Box<Account<'info, CouncilAction>>>,
+
+    #[account(
+        constraint = Some(council.key()) == collection_details.council @ Errors::NotCouncilMember
+    )]
+    pub council: Option<Box<Account<'info, Council>>>,
+
+    /// CHECK: Validated against the creator stored in the council action
+    #[account(mut)]
+    pub action_creator: Option<AccountInfo<'info>>
+}
+
+/// * The vault is funded with fund_co_reward, the authority gets the tokens left when the collection is closed
+/// * the co-reward starts without emission, it's set with edit_co_reward (after the timelock)
+pub fn add_co_reward_vault_handler(ctx: Context<AddCoRewardVault>, authority: Pubkey) -> Result<()> {
+    CouncilAction::require_approved(
+        &ctx.accounts.collection_details,
+        ctx.accounts.council.as_deref(),
+        ctx.accounts.council_action.as_deref(),
+        ctx.accounts.action_creator.as_ref(),
+        |action| matches!(
+            action,
+            CouncilActionType::AddCoRewardVault { token, authority: approved }
+            if *token == ctx.accounts.token.key() && *approved == authority
+        )
+    )?;
+
+    let co_reward = CoReward::new(
+        ctx.accounts.token.key(),
+        ctx.accounts.token.decimals,
+        Some(ctx.accounts.reward_vault.key()),
+        authority,
+        0,
+        0
+    );
+
+    ctx.accounts.collection_details.add_co_reward(co_reward)
+}
@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::MetadataAccount,
+    token_interface::{
+        Mint,
+        TokenAccount,
+        TokenInterface
+    }
+};
+use crate::{states::{Collection,Council,CouncilAction,CouncilActionType},Errors};
+
+#[derive(Accounts)]
+pub struct AddRewardVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"collection", verified_collection.key().as_ref()],
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin
+    )]
+    pub collection_details: Account<'info, Collection>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
+    )]
+    pub verified_collection: Account<'info, MetadataAccount>,
+
+    pub token: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA used as the owner of the reward vault
+    #[account(
+        seeds = [b"token-authority", collection_details.key().as_ref()],
+        bump = collection_details.token_authority_bump
+    )]
+    pub token_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"reward-vault", collection_details.key().as_ref()],
+        bump,
+        token::mint = token,
+        token::authority = token_authority,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// The approved AddRewardVault action (if the collection is managed by a council)
+    #[account(
+        mut,
+        constraint = Some(council_action.council) == collection_details.council @ Errors::CouncilActionMismatch,
+        constraint = council_action.collection == collection_details.key() @ Errors::CouncilActionMismatch,
+        close = action_creator
+    )]
+    pub council_action: Option<Box<Account<'info, CouncilAction>>>,
+
+    #[account(
+        constraint = Some(council.key()) == collection_details.council @ Errors::NotCouncilMember
+    )]
+    pub council: Option<Box<Account<'info, Council>>>,
+
+    /// CHECK: Validated against the creator stored in the council action
+    #[account(mut)]
+    pub action_creator: Option<AccountInfo<'info>>
+}
+
+/// * The mint authority stays with its holder, the vault is funded with fund_reward_vault
+pub fn add_reward_vault_handler(ctx: Context<AddRewardVault>) -> Result<()> {
+    CouncilAction::require_approved(
+        &ctx.accounts.collection_details,
+        ctx.accounts.council.as_deref(),
+        ctx.accounts.council_action.as_deref(),
+        ctx.accounts.action_creator.as_ref(),
+        |action| matches!(action, CouncilActionType::AddRewardVault { token } if *token == ctx.accounts.token.key())
+    )?;
+
+    let token_mint = ctx.accounts.token.key();
+    let token_decimals = ctx.accounts.token.decimals;
+    let reward_vault = ctx.accounts.reward_vault.key();
+
+    ctx.accounts.collection_details.add_reward_vault(token_mint, token_decimals, reward_vault)
+}
@@ -1,44 +1,35 @@
 use anchor_lang::prelude::*;
 use anchor_spl::metadata::MetadataAccount;
-use crate::{states::Collection,Errors};
+use crate::{states::{Collection,StakeMode,EmissionModel},Errors};
 
 #[derive(Accounts)]
 pub struct AddStake<'info> {
     #[account(
         mut,
         seeds = [b"collection", verified_collection.key().as_ref()],
-        bump
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin,
+        constraint = collection_details.council.is_none() @ Errors::CouncilRequired
     )]
     pub collection_details: Account<'info, Collection>,
 
     pub owner: Signer<'info>,
     
     #[account(
-        constraint = verified_collection.collection == None @ Errors::NotCollectionNft,
-        constraint = verified_collection.update_authority == owner.key()
-        @ Errors::NotUpdateAuthority
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
     )]
     pub verified_collection: Account<'info, MetadataAccount>,
 
 }
 
-pub fn add_stake_handler(ctx: Context<AddStake>, emission: u64) -> Result<()> {
+pub fn add_stake_handler(
+    ctx: Context<AddStake>,
+    emission: u64,
+    max_supply: u64,
+    stake_mode: StakeMode,
+    emission_model: EmissionModel
+) -> Result<()> {
     let collection_details = &mut ctx.accounts.collection_details;
 
-    require_eq!(collection_details.is_staking, false, Errors::ModuleAlreadyAdded);
-
-    let token_mint = collection_details.token_mint;
-
-    match token_mint {
-        None => {
-            return Err(Errors::TokenNotFound.into());
-        },
-        _ => ()
-    }
-
-    require_gt!(emission, 0, Errors::ZeroValue);
-
-    collection_details.is_staking = true;
-    collection_details.emission = emission;
-    Ok(())
+    collection_details.add_stake(emission, max_supply, stake_mode, emission_model)
 }
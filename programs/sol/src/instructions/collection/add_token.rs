@@ -1,22 +1,30 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    metadata::MetadataAccount,
-    token::{
+    metadata::{
+        self as token_metadata,
+        Metadata,
+        MetadataAccount,
+        CreateMetadataAccountsV3
+    },
+    token,
+    token_interface::{
         self,
         Mint,
-        Token,
+        TokenInterface,
         SetAuthority,
-        spl_token::instruction::AuthorityType
+        spl_token_2022::instruction::AuthorityType
     }
 };
-use crate::{states::Collection,Errors,ID};
+use mpl_token_metadata::state::DataV2;
+use crate::{states::{Collection,Council,CouncilAction,CouncilActionType},Errors,MPL_TOKEN_METADATA_ID};
 
 #[derive(Accounts)]
 pub struct AddToken<'info> {
     #[account(
         mut,
         seeds = [b"collection", verified_collection.key().as_ref()],
-        bump
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin
     )]
     pub collection_details: Account<'info, Collection>,
 
@@ -24,22 +32,58 @@ pub struct AddToken<'info> {
     pub owner: Signer<'info>,
     
     #[account(
-        constraint = verified_collection.collection == None @ Errors::NotCollectionNft,
-        constraint = verified_collection.update_authority == owner.key()
-        @ Errors::NotUpdateAuthority
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
     )]
     pub verified_collection: Account<'info, MetadataAccount>,
 
     #[account(
-        init,
-        payer = owner,
-        mint::decimals = 9,
+        mut,
         mint::authority = owner,
     )]
-    pub token: Account<'info, Mint>,
+    pub token: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA used as the mint authority (and metadata update authority) of the token
+    #[account(
+        seeds = [b"token-authority", collection_details.key().as_ref()],
+        bump = collection_details.token_authority_bump
+    )]
+    pub token_authority: AccountInfo<'info>,
+
+    /// CHECK: Created by the token metadata program
+    #[account(
+        mut,
+        seeds = [
+            b"metadata", 
+            MPL_TOKEN_METADATA_ID.as_ref(), 
+            token.key().as_ref()
+        ],
+        seeds::program = MPL_TOKEN_METADATA_ID,
+        bump
+    )]
+    pub token_metadata: Option<AccountInfo<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub metadata_program: Option<Program<'info, Metadata>>,
+    pub system_program: Program<'info, System>,
+    pub rent: Option<Sysvar<'info, Rent>>,
+
+    /// The approved AddToken action (if the collection is managed by a council)
+    #[account(
+        mut,
+        constraint = Some(council_action.council) == collection_details.council @ Errors::CouncilActionMismatch,
+        constraint = council_action.collection == collection_details.key() @ Errors::CouncilActionMismatch,
+        close = action_creator
+    )]
+    pub council_action: Option<Box<Account<'info, CouncilAction>>>,
+
+    #[account(
+        constraint = Some(council.key()) == collection_details.council @ Errors::NotCouncilMember
+    )]
+    pub council: Option<Box<Account<'info, Council>>>,
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>
+    /// CHECK: Validated against the creator stored in the council action
+    #[account(mut)]
+    pub action_creator: Option<AccountInfo<'info>>
 }
 
 impl<'info> AddToken<'info> {
@@ -51,9 +95,60 @@ impl<'info> AddToken<'info> {
         };
         CpiContext::new(cpi_program, cpi_accounts)
     }
+
+    /// Creates the metadata of the token, the token-authority PDA becomes its update authority
+    pub fn create_metadata(&self, metadata: TokenMetadataArgs) -> Result<()> {
+        // The metadata CPIs are only exercised with the original token program
+        require_keys_eq!(*self.token.to_account_info().owner, token::ID, Errors::MetadataNotSupported);
+
+        let (token_metadata, metadata_program, rent) = match (
+            self.token_metadata.as_ref(),
+            self.metadata_program.as_ref(),
+            self.rent.as_ref()
+        ) {
+            (Some(token_metadata), Some(metadata_program), Some(rent)) => {
+                (token_metadata, metadata_program, rent)
+            },
+            _ => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
+
+        let cpi_program = metadata_program.to_account_info();
+        let cpi_accounts = CreateMetadataAccountsV3 {
+            metadata: token_metadata.to_account_info(),
+            mint: self.token.to_account_info(),
+            mint_authority: self.owner.to_account_info(),
+            payer: self.owner.to_account_info(),
+            update_authority: self.token_authority.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            rent: rent.to_account_info()
+        };
+
+        token_metadata::create_metadata_accounts_v3(
+            CpiContext::new(cpi_program, cpi_accounts),
+            metadata.into_data(),
+            true,
+            false,
+            None
+        )
+    }
 }
 
-pub fn add_token_handler(ctx: Context<AddToken>) -> Result<()> {
+/// * Creates the token metadata only if provided (an existing mint may have it already)
+pub fn add_token_handler(ctx: Context<AddToken>, metadata: Option<TokenMetadataArgs>) -> Result<()> {
+    CouncilAction::require_approved(
+        &ctx.accounts.collection_details,
+        ctx.accounts.council.as_deref(),
+        ctx.accounts.council_action.as_deref(),
+        ctx.accounts.action_creator.as_ref(),
+        |action| matches!(
+            action,
+            CouncilActionType::AddToken { token, metadata: approved }
+            if *token == ctx.accounts.token.key() && *approved == metadata
+        )
+    )?;
+
     let token_mint = ctx.accounts.collection_details.token_mint;
 
     match token_mint {
@@ -61,9 +156,14 @@ pub fn add_token_handler(ctx: Context<AddToken>) -> Result<()> {
             return Err(Errors::TokenAlreadyExists.into());
         },
         None => {
-            let (token_authority, _) = Pubkey::find_program_address(&[b"token-authority"], &ID);
+            // The metadata is created while the signer is still the mint authority
+            if let Some(metadata) = metadata {
+                ctx.accounts.create_metadata(metadata)?;
+            }
+
+            let token_authority = ctx.accounts.token_authority.key();
 
-            token::set_authority(
+            token_interface::set_authority(
                 ctx.accounts.set_authority_context(), 
                 AuthorityType::MintTokens, 
                 Some(token_authority)
@@ -72,8 +172,30 @@ pub fn add_token_handler(ctx: Context<AddToken>) -> Result<()> {
             let collection_details = &mut ctx.accounts.collection_details;
 
             collection_details.token_mint = Some(ctx.accounts.token.key());  
+            collection_details.token_decimals = ctx.accounts.token.decimals;
         }
     }
 
     Ok(())
 }
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,PartialEq,Eq)]
+pub struct TokenMetadataArgs {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String
+}
+
+impl TokenMetadataArgs {
+    pub fn into_data(self) -> DataV2 {
+        DataV2 {
+            name: self.name,
+            symbol: self.symbol,
+            uri: self.uri,
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None
+        }
+    }
+}
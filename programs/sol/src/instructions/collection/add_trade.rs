@@ -7,16 +7,16 @@ pub struct AddTrade<'info> {
     #[account(
         mut,
         seeds = [b"collection", verified_collection.key().as_ref()],
-        bump
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin,
+        constraint = collection_details.council.is_none() @ Errors::CouncilRequired
     )]
     pub collection_details: Account<'info, Collection>,
 
     pub owner: Signer<'info>,
     
     #[account(
-        constraint = verified_collection.collection == None @ Errors::NotCollectionNft,
-        constraint = verified_collection.update_authority == owner.key()
-        @ Errors::NotUpdateAuthority
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
     )]
     pub verified_collection: Account<'info, MetadataAccount>,
 
@@ -25,12 +25,5 @@ pub struct AddTrade<'info> {
 pub fn add_trade_handler(ctx: Context<AddTrade>, trade_fee: u64, duration: i64) -> Result<()> {
     let collection_details = &mut ctx.accounts.collection_details;
 
-    require_eq!(collection_details.is_trade, false, Errors::ModuleAlreadyAdded);
-    
-    require_gt!(duration, 0, Errors::ZeroValue);
-
-    collection_details.is_trade = true;
-    collection_details.trade_duration = duration;
-    collection_details.trade_fees = trade_fee;
-    Ok(())
+    collection_details.add_trade(trade_fee, duration)
 }
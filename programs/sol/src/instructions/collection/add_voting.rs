@@ -7,16 +7,16 @@ pub struct AddVoting<'info> {
     #[account(
         mut,
         seeds = [b"collection", verified_collection.key().as_ref()],
-        bump
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin,
+        constraint = collection_details.council.is_none() @ Errors::CouncilRequired
     )]
     pub collection_details: Account<'info, Collection>,
 
     pub owner: Signer<'info>,
     
     #[account(
-        constraint = verified_collection.collection == None @ Errors::NotCollectionNft,
-        constraint = verified_collection.update_authority == owner.key()
-        @ Errors::NotUpdateAuthority
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
     )]
     pub verified_collection: Account<'info, MetadataAccount>,
 
@@ -25,17 +25,5 @@ pub struct AddVoting<'info> {
 pub fn add_voting_handler(ctx: Context<AddVoting>, count: u64, duration: i64, quorum: u64) -> Result<()> {
     let collection_details = &mut ctx.accounts.collection_details;
 
-    require_eq!(collection_details.is_voting, false, Errors::ModuleAlreadyAdded);
-
-    require_gt!(count, 0, Errors::ZeroValue);
-    require_gt!(duration, 0, Errors::ZeroValue);
-    require_gt!(quorum, 0, Errors::ZeroValue);
-
-    require_gte!(count, quorum, Errors::InvalidQuorum);
-
-    collection_details.is_voting = true;
-    collection_details.quorum = quorum;
-    collection_details.count = count;
-    collection_details.vote_duration = duration;
-    Ok(())
+    collection_details.add_voting(count, duration, quorum)
 }
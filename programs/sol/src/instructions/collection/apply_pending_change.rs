@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::{states::{Collection,PendingChange},Errors};
+
+#[derive(Accounts)]
+pub struct ApplyPendingChange<'info> {
+    #[account(
+        mut,
+        has_one = collection,
+        has_one = creator,
+        close = creator
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    #[account(mut)]
+    pub collection: Account<'info, Collection>,
+
+    /// CHECK: Validated against the creator stored in the pending change
+    #[account(mut)]
+    pub creator: AccountInfo<'info>,
+
+    pub signer: Signer<'info>,
+}
+
+pub fn apply_pending_change_handler(ctx: Context<ApplyPendingChange>) -> Result<()> {
+    let pending_change = &ctx.accounts.pending_change;
+    let collection = &mut ctx.accounts.collection;
+
+    let clock = Clock::get()?;
+    let current_unix = clock.unix_timestamp;
+
+    require_gte!(current_unix, pending_change.apply_time, Errors::TimelockNotExpired);
+
+    collection.apply_change(&pending_change.change)
+}
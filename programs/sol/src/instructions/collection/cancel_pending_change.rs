@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::{
+    states::{Collection,PendingChange,Council,CouncilAction,CouncilActionType},
+    Errors
+};
+
+#[derive(Accounts)]
+pub struct CancelPendingChange<'info> {
+    #[account(
+        mut,
+        has_one = collection,
+        has_one = creator,
+        close = creator
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    #[account(
+        constraint = collection.admin == owner.key() @ Errors::NotAdmin
+    )]
+    pub collection: Account<'info, Collection>,
+
+    /// CHECK: Validated against the creator stored in the pending change
+    #[account(mut)]
+    pub creator: AccountInfo<'info>,
+
+    pub owner: Signer<'info>,
+
+    /// The approved CancelPendingChange action (if the collection is managed by a council)
+    #[account(
+        mut,
+        constraint = Some(council_action.council) == collection.council @ Errors::CouncilActionMismatch,
+        constraint = council_action.collection == collection.key() @ Errors::CouncilActionMismatch,
+        close = action_creator
+    )]
+    pub council_action: Option<Box<Account<'info, CouncilAction>>>,
+
+    #[account(
+        constraint = Some(council.key()) == collection.council @ Errors::NotCouncilMember
+    )]
+    pub council: Option<Box<Account<'info, Council>>>,
+
+    /// CHECK: Validated against the creator stored in the council action
+    #[account(mut)]
+    pub action_creator: Option<AccountInfo<'info>>
+}
+
+/// With a council, the approved CancelPendingChange action is required (and closed to its creator)
+pub fn cancel_pending_change_handler(ctx: Context<CancelPendingChange>) -> Result<()> {
+    let pending_change = ctx.accounts.pending_change.key();
+
+    CouncilAction::require_approved(
+        &ctx.accounts.collection,
+        ctx.accounts.council.as_deref(),
+        ctx.accounts.council_action.as_deref(),
+        ctx.accounts.action_creator.as_ref(),
+        |action| matches!(
+            action,
+            CouncilActionType::CancelPendingChange { pending_change: approved } if *approved == pending_change
+        )
+    )
+}
@@ -0,0 +1,312 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::MetadataAccount,
+    token_interface::{
+        self,
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        SetAuthority,
+        TransferChecked,
+        CloseAccount,
+        spl_token_2022::instruction::AuthorityType
+    }
+};
+use crate::{
+    states::{Collection,CoReward,TreasuryVault,Council,CouncilAction,CouncilActionType},
+    Errors,
+    ID,
+    utils
+};
+
+#[derive(Accounts)]
+pub struct CloseCollection<'info> {
+    #[account(
+        mut,
+        seeds = [b"collection", verified_collection.key().as_ref()],
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin,
+        close = owner
+    )]
+    pub collection_details: Account<'info, Collection>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
+    )]
+    pub verified_collection: Account<'info, MetadataAccount>,
+
+    #[account(
+        mut,
+        constraint = Some(token_mint.key()) == collection_details.token_mint @ Errors::TokenNotFound
+    )]
+    pub token_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: PDA used as the mint authority of the collection token
+    #[account(
+        seeds = [b"token-authority", collection_details.key().as_ref()],
+        bump = collection_details.token_authority_bump
+    )]
+    pub token_authority: Option<AccountInfo<'info>>,
+
+    /// The reward vault of the collection (if the rewards are paid from it)
+    #[account(
+        mut,
+        constraint = Some(reward_vault.key()) == collection_details.reward_vault @ Errors::RewardVaultNotFound
+    )]
+    pub reward_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the tokens left in the reward vault
+    #[account(
+        mut,
+        constraint = Some(token_receive_address.mint) == collection_details.token_mint @ Errors::TokenNotFound
+    )]
+    pub token_receive_address: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: The treasury vault of the collection, closed to the admin if it was created
+    #[account(
+        mut,
+        seeds = [b"treasury", collection_details.key().as_ref()],
+        bump
+    )]
+    pub treasury_vault: AccountInfo<'info>,
+
+    /// The approved CloseCollection action (if the collection is managed by a council)
+    #[account(
+        mut,
+        has_one = creator,
+        constraint = Some(council_action.council) == collection_details.council @ Errors::CouncilActionMismatch,
+        constraint = council_action.collection == collection_details.key() @ Errors::CouncilActionMismatch,
+        close = creator
+    )]
+    pub council_action: Option<Box<Account<'info, CouncilAction>>>,
+
+    #[account(
+        constraint = Some(council.key()) == collection_details.council @ Errors::NotCouncilMember
+    )]
+    pub council: Option<Box<Account<'info, Council>>>,
+
+    /// CHECK: Validated against the creator stored in the council action
+    #[account(mut)]
+    pub creator: Option<AccountInfo<'info>>
+}
+
+impl<'info> CloseCollection<'info> {
+    /// Hands the mint authority of the collection token back to the admin
+    pub fn return_mint_authority(&self) -> Result<()> {
+        let collection_key = self.collection_details.key();
+        let token_seed = &[
+            &b"token-authority"[..],
+            collection_key.as_ref(),
+            &[self.collection_details.token_authority_bump]
+        ];
+
+        let (token_mint, token_authority, token_program) = match (
+            self.token_mint.as_ref(),
+            self.token_authority.as_ref(),
+            self.token_program.as_ref()
+        ) {
+            (Some(token_mint), Some(token_authority), Some(token_program)) => {
+                (token_mint, token_authority, token_program)
+            },
+            _ => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
+
+        let cpi_accounts = SetAuthority {
+            account_or_mint: token_mint.to_account_info(),
+            current_authority: token_authority.to_account_info()
+        };
+        let cpi_context = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+
+        token_interface::set_authority(
+            cpi_context.with_signer(&[&token_seed[..]]),
+            AuthorityType::MintTokens,
+            Some(self.owner.key())
+        )
+    }
+
+    /// Sends the tokens left in the reward vault to the admin and closes the vault
+    pub fn close_reward_vault(&self) -> Result<()> {
+        let collection_key = self.collection_details.key();
+        let token_seed = &[
+            &b"token-authority"[..],
+            collection_key.as_ref(),
+            &[self.collection_details.token_authority_bump]
+        ];
+
+        let (token_mint, token_authority, token_program, reward_vault, token_receive_address) = match (
+            self.token_mint.as_ref(),
+            self.token_authority.as_ref(),
+            self.token_program.as_ref(),
+            self.reward_vault.as_ref(),
+            self.token_receive_address.as_ref()
+        ) {
+            (
+                Some(token_mint),
+                Some(token_authority),
+                Some(token_program),
+                Some(reward_vault),
+                Some(token_receive_address)
+            ) => {
+                (token_mint, token_authority, token_program, reward_vault, token_receive_address)
+            },
+            _ => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
+
+        if reward_vault.amount > 0 {
+            let cpi_accounts = TransferChecked {
+                from: reward_vault.to_account_info(),
+                mint: token_mint.to_account_info(),
+                to: token_receive_address.to_account_info(),
+                authority: token_authority.to_account_info()
+            };
+            let cpi_context = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+
+            token_interface::transfer_checked(
+                cpi_context.with_signer(&[&token_seed[..]]),
+                reward_vault.amount,
+                token_mint.decimals
+            )?;
+        }
+
+        // The fees withheld in the vault would block closing it
+        utils::harvest_withheld_fees(
+            &token_program.to_account_info(),
+            &token_mint.to_account_info(),
+            &reward_vault.to_account_info()
+        )?;
+
+        let cpi_accounts = CloseAccount {
+            account: reward_vault.to_account_info(),
+            destination: self.owner.to_account_info(),
+            authority: token_authority.to_account_info()
+        };
+        let cpi_context = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+
+        token_interface::close_account(cpi_context.with_signer(&[&token_seed[..]]))
+    }
+}
+
+/// Hands the mint authority of the co-reward back to its authority, or sends it the tokens left in the vault
+fn close_co_reward<'info>(
+    co_reward: &CoReward,
+    co_reward_accounts: &utils::CoRewardAccounts<'info>,
+    token_authority: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+    token_seed: &[&[u8]]
+) -> Result<()> {
+    let reward_vault = match co_reward_accounts.reward_vault.as_ref() {
+        Some(reward_vault) => reward_vault,
+        None => {
+            let cpi_accounts = SetAuthority {
+                account_or_mint: co_reward_accounts.mint.clone(),
+                current_authority: token_authority.clone()
+            };
+            let cpi_context = CpiContext::new(co_reward_accounts.token_program.clone(), cpi_accounts);
+
+            return token_interface::set_authority(
+                cpi_context.with_signer(&[token_seed]),
+                AuthorityType::MintTokens,
+                Some(co_reward.authority)
+            );
+        }
+    };
+
+    if reward_vault.amount > 0 {
+        co_reward_accounts.pay(reward_vault.amount, co_reward.decimals, token_authority, token_seed)?;
+    }
+
+    // The fees withheld in the vault would block closing it
+    utils::harvest_withheld_fees(
+        &co_reward_accounts.token_program,
+        &co_reward_accounts.mint,
+        &reward_vault.to_account_info()
+    )?;
+
+    let cpi_accounts = CloseAccount {
+        account: reward_vault.to_account_info(),
+        destination: owner.clone(),
+        authority: token_authority.clone()
+    };
+    let cpi_context = CpiContext::new(co_reward_accounts.token_program.clone(), cpi_accounts);
+
+    token_interface::close_account(cpi_context.with_signer(&[token_seed]))
+}
+
+/// * Fails while any NFT is staked or any trade is open
+/// * with a council, the approved CloseCollection action is required (and closed to its creator)
+/// * the lamports of the treasury vault go to the admin
+/// * the proposals and identities can be closed afterwards by anyone
+/// * the stakes and trades never migrated (not counted as open) are released afterwards by anyone
+///   with release_legacy_stake and release_legacy_trade
+/// * each co-reward passes its accounts (as in withdraw_tokens, with the token account of its authority)
+pub fn close_collection_handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseCollection<'info>>) -> Result<()> {
+    let collection_details = &ctx.accounts.collection_details;
+
+    if collection_details.council.is_some() {
+        let (council, council_action) = match (
+            ctx.accounts.council.as_ref(),
+            ctx.accounts.council_action.as_ref()
+        ) {
+            (Some(council), Some(council_action)) => (council, council_action),
+            _ => {
+                return Err(Errors::CouncilRequired.into());
+            }
+        };
+
+        require!(
+            matches!(council_action.action, CouncilActionType::CloseCollection),
+            Errors::CouncilActionMismatch
+        );
+        council_action.check_threshold(council)?;
+    }
+
+    require_eq!(collection_details.open_stakes, 0, Errors::StakesOpen);
+    require_eq!(collection_details.open_trades, 0, Errors::TradesOpen);
+
+    if collection_details.reward_vault.is_some() {
+        ctx.accounts.close_reward_vault()?;
+    } else if collection_details.token_mint.is_some() {
+        ctx.accounts.return_mint_authority()?;
+    }
+
+    if ctx.accounts.treasury_vault.owner == &ID {
+        let treasury_vault: Account<TreasuryVault> = Account::try_from(&ctx.accounts.treasury_vault)?;
+        treasury_vault.close(ctx.accounts.owner.to_account_info())?;
+    }
+
+    if !collection_details.co_rewards.is_empty() {
+        let token_authority = match ctx.accounts.token_authority.as_ref() {
+            Some(token_authority) => token_authority.to_account_info(),
+            None => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
+
+        let collection_key = collection_details.key();
+        let token_seed = &[
+            &b"token-authority"[..],
+            collection_key.as_ref(),
+            &[collection_details.token_authority_bump]
+        ];
+
+        let owner = ctx.accounts.owner.to_account_info();
+        let remaining_accounts = &mut ctx.remaining_accounts.iter();
+
+        for co_reward in collection_details.co_rewards.iter() {
+            let co_reward_accounts = utils::CoRewardAccounts::next(remaining_accounts, co_reward, &co_reward.authority)?;
+
+            close_co_reward(co_reward, &co_reward_accounts, &token_authority, &owner, &token_seed[..])?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{metadata::MetadataAccount,token::Mint};
-use crate::{states::Collection,Errors,MPL_TOKEN_METADATA_ID};
+use crate::{states::{Collection,AuthorityBumps},Errors,MPL_TOKEN_METADATA_ID};
 
 #[derive(Accounts)]
 pub struct CreateCollection<'info> {
@@ -24,7 +24,7 @@ pub struct CreateCollection<'info> {
         ],
         seeds::program = MPL_TOKEN_METADATA_ID,
         bump,
-        constraint = verified_collection.collection == None @Errors::NotCollectionNft,
+        constraint = verified_collection.collection.is_none() @Errors::NotCollectionNft,
         constraint = verified_collection.update_authority == owner.key()
         @ Errors::NotUpdateAuthority
     )]
@@ -36,11 +36,17 @@ pub struct CreateCollection<'info> {
 }
 
 pub fn create_collection_handler(ctx: Context<CreateCollection>,treasury: Pubkey) -> Result<()> {
+    require_keys_neq!(treasury, Pubkey::default(), Errors::InvalidTreasury);
+
     let collection_details = &mut ctx.accounts.collection_details;
     let verified_collection_key = ctx.accounts.mint.key();
+    let admin = ctx.accounts.owner.key();
+    let authority_bumps = AuthorityBumps::find(&collection_details.key());
 
     **collection_details = Collection::new(
         verified_collection_key, 
-        treasury);
+        admin,
+        treasury,
+        authority_bumps);
     Ok(())
 }
\ No newline at end of file
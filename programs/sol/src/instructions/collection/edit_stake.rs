@@ -1,33 +1,49 @@
 use anchor_lang::prelude::*;
 use anchor_spl::metadata::MetadataAccount;
-use crate::{states::Collection,Errors};
+use crate::{states::{Collection,PendingChange,ConfigChange},Errors};
 
 #[derive(Accounts)]
 pub struct EditStake<'info> {
     #[account(
-        mut,
         seeds = [b"collection", verified_collection.key().as_ref()],
-        bump
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin,
+        constraint = collection_details.council.is_none() @ Errors::CouncilRequired
     )]
     pub collection_details: Account<'info, Collection>,
 
+    #[account(
+        init,
+        payer = owner,
+        space = PendingChange::LEN
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    #[account(mut)]
     pub owner: Signer<'info>,
     
     #[account(
-        constraint = verified_collection.collection == None @ Errors::NotCollectionNft,
-        constraint = verified_collection.update_authority == owner.key()
-        @ Errors::NotUpdateAuthority
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
     )]
     pub verified_collection: Account<'info, MetadataAccount>,
 
+    pub system_program: Program<'info, System>
 }
 
+/// Queues the change, which can be applied once the timelock has passed
 pub fn edit_stake_handler(ctx: Context<EditStake>, emission: u64) -> Result<()> {
-    let collection_details = &mut ctx.accounts.collection_details;
+    let collection_details = &ctx.accounts.collection_details;
+    let change = ConfigChange::Stake { emission };
 
-    require_gt!(emission, 0, Errors::ZeroValue);
+    // Validates the change against the current configuration
+    collection_details.clone().apply_change(&change)?;
+
+    *ctx.accounts.pending_change = PendingChange::new(
+        collection_details.key(),
+        change,
+        Collection::TIMELOCK,
+        ctx.accounts.owner.key()
+    );
 
-    collection_details.emission = emission;
-    
     Ok(())
 }
\ No newline at end of file
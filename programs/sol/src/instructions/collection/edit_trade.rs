@@ -1,43 +1,54 @@
 use anchor_lang::prelude::*;
 use anchor_spl::metadata::MetadataAccount;
-use crate::{Errors,states::Collection};
+use crate::{Errors,states::{Collection,PendingChange,ConfigChange}};
 
 #[derive(Accounts)]
 pub struct EditTrade<'info> {
     #[account(
-        mut,
         seeds = [b"collection", verified_collection.key().as_ref()],
-        bump
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin,
+        constraint = collection_details.council.is_none() @ Errors::CouncilRequired
     )]
     pub collection_details: Account<'info, Collection>,
 
+    #[account(
+        init,
+        payer = owner,
+        space = PendingChange::LEN
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    #[account(mut)]
     pub owner: Signer<'info>,
     
     #[account(
-        constraint = verified_collection.collection == None @ Errors::NotCollectionNft,
-        constraint = verified_collection.update_authority == owner.key()
-        @ Errors::NotUpdateAuthority
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
     )]
     pub verified_collection: Account<'info, MetadataAccount>,
 
+    pub system_program: Program<'info, System>
 }
 
+/// Queues the change, which can be applied once the timelock has passed
 pub fn edit_trade_handler(ctx: Context<EditTrade>, new_num: u64, edit_type: TradeEditType) -> Result<()> {
-    let collection_details = &mut ctx.accounts.collection_details;
-
-    match edit_type {
-        TradeEditType::Fee => {
-            collection_details.trade_fees = new_num;
-        },
-        TradeEditType::Duration => {
-            require_gt!(new_num, 0, Errors::ZeroValue);
-            collection_details.trade_duration = new_num as i64;
-        }
-    }
+    let collection_details = &ctx.accounts.collection_details;
+    let change = ConfigChange::Trade { new_num, edit_type };
+
+    // Validates the change against the current configuration
+    collection_details.clone().apply_change(&change)?;
+
+    *ctx.accounts.pending_change = PendingChange::new(
+        collection_details.key(),
+        change,
+        Collection::TIMELOCK,
+        ctx.accounts.owner.key()
+    );
+
     Ok(())
 }
 
-#[derive(AnchorSerialize,AnchorDeserialize)]
+#[derive(AnchorSerialize,AnchorDeserialize,Clone)]
 pub enum TradeEditType {
     Fee,
     Duration,
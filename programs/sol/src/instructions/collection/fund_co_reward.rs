@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    self,
+    Mint,
+    TokenAccount,
+    TokenInterface,
+    TransferChecked
+};
+use crate::{states::Collection,Errors};
+
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct FundCoReward<'info> {
+    #[account(mut)]
+    pub collection_details: Account<'info, Collection>,
+
+    #[account(
+        mut,
+        seeds = [b"co-reward-vault", collection_details.key().as_ref(), token_mint.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = collection_details.co_rewards.get(index as usize).map(|co_reward| co_reward.mint) ==
+            Some(token_mint.key()) @ Errors::InvalidCoReward
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = funder,
+        token::token_program = token_program
+    )]
+    pub token_send_address: InterfaceAccount<'info, TokenAccount>,
+
+    pub funder: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>
+}
+
+impl<'info> FundCoReward<'info> {
+    pub fn transfer_token_context(&self) -> CpiContext<'_,'_,'_,'info, TransferChecked<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.token_send_address.to_account_info(),
+            mint: self.token_mint.to_account_info(),
+            to: self.reward_vault.to_account_info(),
+            authority: self.funder.to_account_info()
+        };
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+/// * Anyone can fund the vault (e.g. the partner), the amount received is added to the emission budget
+pub fn fund_co_reward_handler(ctx: Context<FundCoReward>, index: u8, amount: u64) -> Result<()> {
+    let decimals = ctx.accounts.token_mint.decimals;
+    let balance = ctx.accounts.reward_vault.amount;
+
+    token_interface::transfer_checked(ctx.accounts.transfer_token_context(), amount, decimals)?;
+
+    ctx.accounts.reward_vault.reload()?;
+    let received = ctx.accounts.reward_vault.amount
+        .checked_sub(balance)
+        .ok_or(Errors::MathOverflow)?;
+
+    ctx.accounts.collection_details.co_reward(index)?.fund(received)
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    self,
+    Mint,
+    TokenAccount,
+    TokenInterface,
+    TransferChecked
+};
+use crate::{states::{Collection,Council,CouncilAction,CouncilActionType},Errors};
+
+#[derive(Accounts)]
+pub struct FundRewardVault<'info> {
+    #[account(
+        mut,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin
+    )]
+    pub collection_details: Account<'info, Collection>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-vault", collection_details.key().as_ref()],
+        bump,
+        constraint = Some(reward_vault.key()) == collection_details.reward_vault @ Errors::RewardVaultNotFound
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = Some(token_mint.key()) == collection_details.token_mint @ Errors::TokenNotFound
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = owner,
+        token::token_program = token_program
+    )]
+    pub token_send_address: InterfaceAccount<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// The approved FundRewardVault action (if the collection is managed by a council)
+    #[account(
+        mut,
+        constraint = Some(council_action.council) == collection_details.council @ Errors::CouncilActionMismatch,
+        constraint = council_action.collection == collection_details.key() @ Errors::CouncilActionMismatch,
+        close = action_creator
+    )]
+    pub council_action: Option<Box<Account<'info, CouncilAction>>>,
+
+    #[account(
+        constraint = Some(council.key()) == collection_details.council @ Errors::NotCouncilMember
+    )]
+    pub council: Option<Box<Account<'info, Council>>>,
+
+    /// CHECK: Validated against the creator stored in the council action
+    #[account(mut)]
+    pub action_creator: Option<AccountInfo<'info>>
+}
+
+impl<'info> FundRewardVault<'info> {
+    pub fn transfer_token_context(&self) -> CpiContext<'_,'_,'_,'info, TransferChecked<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.token_send_address.to_account_info(),
+            mint: self.token_mint.to_account_info(),
+            to: self.reward_vault.to_account_info(),
+            authority: self.owner.to_account_info()
+        };
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+/// * Only the amount received by the vault (after any transfer fee) is added to the emission budget
+pub fn fund_reward_vault_handler(ctx: Context<FundRewardVault>, amount: u64) -> Result<()> {
+    CouncilAction::require_approved(
+        &ctx.accounts.collection_details,
+        ctx.accounts.council.as_deref(),
+        ctx.accounts.council_action.as_deref(),
+        ctx.accounts.action_creator.as_ref(),
+        |action| matches!(action, CouncilActionType::FundRewardVault { amount: approved } if *approved == amount)
+    )?;
+
+    let decimals = ctx.accounts.token_mint.decimals;
+    let balance = ctx.accounts.reward_vault.amount;
+
+    token_interface::transfer_checked(ctx.accounts.transfer_token_context(), amount, decimals)?;
+
+    ctx.accounts.reward_vault.reload()?;
+    let received = ctx.accounts.reward_vault.amount
+        .checked_sub(balance)
+        .ok_or(Errors::MathOverflow)?;
+
+    ctx.accounts.collection_details.fund_reward_vault(received)
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use crate::{states::{Collection,TreasuryVault,Council,CouncilAction,CouncilActionType},Errors};
+
+#[derive(Accounts)]
+pub struct InitTreasuryVault<'info> {
+    #[account(
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin
+    )]
+    pub collection_details: Account<'info, Collection>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = TreasuryVault::LEN,
+        seeds = [b"treasury", collection_details.key().as_ref()],
+        bump
+    )]
+    pub treasury_vault: Account<'info, TreasuryVault>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The approved InitTreasuryVault action (if the collection is managed by a council)
+    #[account(
+        mut,
+        constraint = Some(council_action.council) == collection_details.council @ Errors::CouncilActionMismatch,
+        constraint = council_action.collection == collection_details.key() @ Errors::CouncilActionMismatch,
+        close = action_creator
+    )]
+    pub council_action: Option<Box<Account<'info, CouncilAction>>>,
+
+    #[account(
+        constraint = Some(council.key()) == collection_details.council @ Errors::NotCouncilMember
+    )]
+    pub council: Option<Box<Account<'info, Council>>>,
+
+    /// CHECK: Validated against the creator stored in the council action
+    #[account(mut)]
+    pub action_creator: Option<AccountInfo<'info>>
+}
+
+/// Creates the vault only, the treasury address is switched to it with update_treasury
+pub fn init_treasury_vault_handler(ctx: Context<InitTreasuryVault>) -> Result<()> {
+    CouncilAction::require_approved(
+        &ctx.accounts.collection_details,
+        ctx.accounts.council.as_deref(),
+        ctx.accounts.council_action.as_deref(),
+        ctx.accounts.action_creator.as_ref(),
+        |action| matches!(action, CouncilActionType::InitTreasuryVault)
+    )?;
+
+    let collection = ctx.accounts.collection_details.key();
+
+    *ctx.accounts.treasury_vault = TreasuryVault::new(collection);
+
+    Ok(())
+}
@@ -6,6 +6,30 @@ mod edit_voting;
 mod edit_trade;
 mod edit_stake;
 mod add_token;
+mod add_reward_vault;
+mod fund_reward_vault;
+mod add_co_reward;
+mod add_co_reward_vault;
+mod fund_co_reward;
+mod edit_co_reward;
+mod update_token_metadata;
+mod remove_voting;
+mod remove_trade;
+mod remove_stake;
+mod top_up_emission;
+mod set_rarity_root;
+mod set_lock_tiers;
+mod set_emission_schedule;
+mod set_unbonding_period;
+mod apply_pending_change;
+mod cancel_pending_change;
+mod update_treasury;
+mod init_treasury_vault;
+mod withdraw_treasury;
+mod close_collection;
+mod set_pause;
+mod propose_admin;
+mod accept_admin;
 
 pub use create_collection::*;
 pub use add_voting::*;
@@ -14,4 +38,28 @@ pub use add_stake::*;
 pub use edit_stake::*;
 pub use edit_voting::*;
 pub use edit_trade::*;
-pub use add_token::*;
\ No newline at end of file
+pub use add_token::*;
+pub use add_reward_vault::*;
+pub use fund_reward_vault::*;
+pub use add_co_reward::*;
+pub use add_co_reward_vault::*;
+pub use fund_co_reward::*;
+pub use edit_co_reward::*;
+pub use update_token_metadata::*;
+pub use remove_voting::*;
+pub use remove_trade::*;
+pub use remove_stake::*;
+pub use top_up_emission::*;
+pub use set_rarity_root::*;
+pub use set_lock_tiers::*;
+pub use set_emission_schedule::*;
+pub use set_unbonding_period::*;
+pub use apply_pending_change::*;
+pub use cancel_pending_change::*;
+pub use update_treasury::*;
+pub use init_treasury_vault::*;
+pub use withdraw_treasury::*;
+pub use close_collection::*;
+pub use set_pause::*;
+pub use propose_admin::*;
+pub use accept_admin::*;
\ No newline at end of file
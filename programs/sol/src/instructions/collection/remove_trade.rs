@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+use crate::{states::Collection,Errors};
+
+#[derive(Accounts)]
+pub struct RemoveTrade<'info> {
+    #[account(
+        mut,
+        seeds = [b"collection", verified_collection.key().as_ref()],
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin,
+        constraint = collection_details.council.is_none() @ Errors::CouncilRequired
+    )]
+    pub collection_details: Account<'info, Collection>,
+
+    pub owner: Signer<'info>,
+    
+    #[account(
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
+    )]
+    pub verified_collection: Account<'info, MetadataAccount>,
+
+}
+
+pub fn remove_trade_handler(ctx: Context<RemoveTrade>) -> Result<()> {
+    let collection_details = &mut ctx.accounts.collection_details;
+
+    collection_details.remove_trade()
+}
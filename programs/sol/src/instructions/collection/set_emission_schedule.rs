@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+use crate::{states::{Collection,PendingChange,ConfigChange,EmissionSchedule},Errors};
+
+#[derive(Accounts)]
+pub struct SetEmissionSchedule<'info> {
+    #[account(
+        seeds = [b"collection", verified_collection.key().as_ref()],
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin,
+        constraint = collection_details.council.is_none() @ Errors::CouncilRequired
+    )]
+    pub collection_details: Account<'info, Collection>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = PendingChange::LEN
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
+    )]
+    pub verified_collection: Account<'info, MetadataAccount>,
+
+    pub system_program: Program<'info, System>
+}
+
+/// Queues the change, which can be applied once the timelock has passed
+pub fn set_emission_schedule_handler(
+    ctx: Context<SetEmissionSchedule>,
+    emission_schedule: Option<EmissionSchedule>
+) -> Result<()> {
+    let collection_details = &ctx.accounts.collection_details;
+    let change = ConfigChange::EmissionSchedule { emission_schedule };
+
+    // Validates the change against the current configuration
+    collection_details.clone().apply_change(&change)?;
+
+    *ctx.accounts.pending_change = PendingChange::new(
+        collection_details.key(),
+        change,
+        Collection::TIMELOCK,
+        ctx.accounts.owner.key()
+    );
+
+    Ok(())
+}
\ No newline at end of file
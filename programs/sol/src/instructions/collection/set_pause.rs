@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+use crate::{states::{Collection,PausableOperation,Council,CouncilAction,CouncilActionType},Errors};
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"collection", verified_collection.key().as_ref()],
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin
+    )]
+    pub collection_details: Account<'info, Collection>,
+
+    pub owner: Signer<'info>,
+    
+    #[account(
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
+    )]
+    pub verified_collection: Account<'info, MetadataAccount>,
+
+    /// The approved SetPause action (if the collection is managed by a council)
+    #[account(
+        mut,
+        constraint = Some(council_action.council) == collection_details.council @ Errors::CouncilActionMismatch,
+        constraint = council_action.collection == collection_details.key() @ Errors::CouncilActionMismatch,
+        close = action_creator
+    )]
+    pub council_action: Option<Box<Account<'info, CouncilAction>>>,
+
+    #[account(
+        constraint = Some(council.key()) == collection_details.council @ Errors::NotCouncilMember
+    )]
+    pub council: Option<Box<Account<'info, Council>>>,
+
+    /// CHECK: Validated against the creator stored in the council action
+    #[account(mut)]
+    pub action_creator: Option<AccountInfo<'info>>
+}
+
+pub fn set_pause_handler(
+    ctx: Context<SetPause>,
+    is_paused: bool,
+    allowlist: Option<Vec<PausableOperation>>
+) -> Result<()> {
+    CouncilAction::require_approved(
+        &ctx.accounts.collection_details,
+        ctx.accounts.council.as_deref(),
+        ctx.accounts.council_action.as_deref(),
+        ctx.accounts.action_creator.as_ref(),
+        |action| matches!(
+            action,
+            CouncilActionType::SetPause { is_paused: approved, allowlist: approved_allowlist }
+            if *approved == is_paused && *approved_allowlist == allowlist
+        )
+    )?;
+
+    let collection_details = &mut ctx.accounts.collection_details;
+
+    collection_details.is_paused = is_paused;
+
+    if let Some(operations) = allowlist {
+        collection_details.pause_allowlist = operations
+            .iter()
+            .fold(0, |mask, operation| mask | operation.flag());
+    }
+    Ok(())
+}
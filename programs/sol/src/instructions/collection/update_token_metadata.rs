@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::{
+        self as token_metadata,
+        Metadata,
+        MetadataAccount,
+        UpdateMetadataAccountsV2
+    },
+    token,
+    token_interface::Mint
+};
+use crate::{states::{Collection,Council,CouncilAction,CouncilActionType},Errors,MPL_TOKEN_METADATA_ID};
+use super::TokenMetadataArgs;
+
+#[derive(Accounts)]
+pub struct UpdateTokenMetadata<'info> {
+    #[account(
+        seeds = [b"collection", verified_collection.key().as_ref()],
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin
+    )]
+    pub collection_details: Account<'info, Collection>,
+
+    pub owner: Signer<'info>,
+    
+    #[account(
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
+    )]
+    pub verified_collection: Account<'info, MetadataAccount>,
+
+    #[account(
+        constraint = Some(token_mint.key()) == collection_details.token_mint @ Errors::TokenNotFound
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Validated by the seeds (the metadata PDA of the collection token)
+    #[account(
+        mut,
+        seeds = [
+            b"metadata", 
+            MPL_TOKEN_METADATA_ID.as_ref(), 
+            token_mint.key().as_ref()
+        ],
+        seeds::program = MPL_TOKEN_METADATA_ID,
+        bump
+    )]
+    pub token_metadata: AccountInfo<'info>,
+
+    /// CHECK: PDA used as the metadata update authority of the token
+    #[account(
+        seeds = [b"token-authority", collection_details.key().as_ref()],
+        bump = collection_details.token_authority_bump
+    )]
+    pub token_authority: AccountInfo<'info>,
+
+    pub metadata_program: Program<'info, Metadata>,
+
+    /// The approved UpdateTokenMetadata action (if the collection is managed by a council)
+    #[account(
+        mut,
+        constraint = Some(council_action.council) == collection_details.council @ Errors::CouncilActionMismatch,
+        constraint = council_action.collection == collection_details.key() @ Errors::CouncilActionMismatch,
+        close = action_creator
+    )]
+    pub council_action: Option<Box<Account<'info, CouncilAction>>>,
+
+    #[account(
+        constraint = Some(council.key()) == collection_details.council @ Errors::NotCouncilMember
+    )]
+    pub council: Option<Box<Account<'info, Council>>>,
+
+    /// CHECK: Validated against the creator stored in the council action
+    #[account(mut)]
+    pub action_creator: Option<AccountInfo<'info>>
+}
+
+impl<'info> UpdateTokenMetadata<'info> {
+    pub fn update_metadata_context(&self) -> CpiContext<'_,'_,'_,'info, UpdateMetadataAccountsV2<'info>> {
+        let cpi_program = self.metadata_program.to_account_info();
+        let cpi_accounts = UpdateMetadataAccountsV2 {
+            metadata: self.token_metadata.to_account_info(),
+            update_authority: self.token_authority.to_account_info()
+        };
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+pub fn update_token_metadata_handler(
+    ctx: Context<UpdateTokenMetadata>,
+    metadata: TokenMetadataArgs
+) -> Result<()> {
+    CouncilAction::require_approved(
+        &ctx.accounts.collection_details,
+        ctx.accounts.council.as_deref(),
+        ctx.accounts.council_action.as_deref(),
+        ctx.accounts.action_creator.as_ref(),
+        |action| matches!(action, CouncilActionType::UpdateTokenMetadata { metadata: approved } if *approved == metadata)
+    )?;
+
+    // The metadata CPIs are only exercised with the original token program
+    require_keys_eq!(*ctx.accounts.token_mint.to_account_info().owner, token::ID, Errors::MetadataNotSupported);
+
+    let collection_details = &ctx.accounts.collection_details;
+    let collection_key = collection_details.key();
+    let token_seed = &[
+        &b"token-authority"[..],
+        collection_key.as_ref(),
+        &[collection_details.token_authority_bump]
+    ];
+
+    token_metadata::update_metadata_accounts_v2(
+        ctx.accounts.update_metadata_context().with_signer(&[&token_seed[..]]),
+        None,
+        Some(metadata.into_data()),
+        None,
+        None
+    )
+}
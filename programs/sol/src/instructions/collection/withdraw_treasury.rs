@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::{states::{Collection,TreasuryVault},Errors};
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin,
+        constraint = collection_details.council.is_none() @ Errors::CouncilRequired
+    )]
+    pub collection_details: Account<'info, Collection>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury", collection_details.key().as_ref()],
+        bump
+    )]
+    pub treasury_vault: Account<'info, TreasuryVault>,
+
+    /// CHECK: Only receives lamports
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn withdraw_treasury_handler(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+    TreasuryVault::withdraw(
+        &ctx.accounts.treasury_vault.to_account_info(),
+        &ctx.accounts.destination,
+        amount
+    )
+}
\ No newline at end of file
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::states::{Council,CouncilAction};
+
+#[derive(Accounts)]
+pub struct ApproveCouncilAction<'info> {
+    #[account(
+        mut,
+        has_one = council
+    )]
+    pub council_action: Box<Account<'info, CouncilAction>>,
+
+    pub council: Account<'info, Council>,
+
+    pub signer: Signer<'info>,
+}
+
+/// The approvals of the other members (signing the same transaction) are
+/// taken from the remaining accounts
+pub fn approve_council_action_handler(ctx: Context<ApproveCouncilAction>) -> Result<()> {
+    let council = &ctx.accounts.council;
+    let council_action = &mut ctx.accounts.council_action;
+
+    council_action.approve(council, &ctx.accounts.signer.to_account_info())?;
+
+    for member in ctx.remaining_accounts.iter() {
+        council_action.approve(council, member)?;
+    }
+
+    Ok(())
+}
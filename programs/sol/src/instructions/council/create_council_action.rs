@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use crate::states::{Council,CouncilAction,CouncilActionType};
+
+#[derive(Accounts)]
+pub struct CreateCouncilAction<'info> {
+    #[account(
+        init,
+        payer = signer,
+        space = CouncilAction::LEN
+    )]
+    pub council_action: Box<Account<'info, CouncilAction>>,
+
+    pub council: Account<'info, Council>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>
+}
+
+/// The approvals of the other members (signing the same transaction) are
+/// taken from the remaining accounts
+pub fn create_council_action_handler(
+    ctx: Context<CreateCouncilAction>,
+    action: CouncilActionType
+) -> Result<()> {
+    let council = &ctx.accounts.council;
+    let council_action = &mut ctx.accounts.council_action;
+
+    if let CouncilActionType::UpdateCouncil { members, threshold } = &action {
+        Council::validate(members, *threshold)?;
+    }
+
+    ***council_action = CouncilAction::new(
+        council.key(),
+        council.collection,
+        action,
+        ctx.accounts.signer.key()
+    );
+
+    council_action.approve(council, &ctx.accounts.signer.to_account_info())?;
+
+    for member in ctx.remaining_accounts.iter() {
+        council_action.approve(council, member)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+use crate::states::{Collection,Council,CouncilAction,CouncilActionType,PendingChange,ConfigChange,TreasuryVault};
+use crate::Errors;
+
+#[derive(Accounts)]
+pub struct ExecuteCouncilAction<'info> {
+    #[account(
+        mut,
+        has_one = council,
+        has_one = collection,
+        has_one = creator,
+        close = creator
+    )]
+    pub council_action: Box<Account<'info, CouncilAction>>,
+
+    #[account(
+        mut,
+        has_one = collection
+    )]
+    pub council: Box<Account<'info, Council>>,
+
+    #[account(
+        mut,
+        constraint = collection.council == Some(council.key()) @ Errors::NotCouncilMember
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+
+    /// CHECK: Validated against the creator stored in the action
+    #[account(mut)]
+    pub creator: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = signer,
+        space = PendingChange::LEN
+    )]
+    pub pending_change: Option<Box<Account<'info, PendingChange>>>,
+
+    /// The treasury vault of the collection (for WithdrawTreasury)
+    #[account(
+        mut,
+        seeds = [b"treasury", collection.key().as_ref()],
+        bump
+    )]
+    pub treasury_vault: Option<Box<Account<'info, TreasuryVault>>>,
+
+    /// CHECK: Validated against the destination of WithdrawTreasury
+    #[account(mut)]
+    pub destination: Option<AccountInfo<'info>>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>
+}
+
+pub fn execute_council_action_handler(ctx: Context<ExecuteCouncilAction>) -> Result<()> {
+    let council = &mut ctx.accounts.council;
+    let collection = &mut ctx.accounts.collection;
+    let council_action = &ctx.accounts.council_action;
+
+    council_action.check_threshold(council)?;
+
+    let mut change = None;
+
+    match council_action.action.clone() {
+        CouncilActionType::AddVoting { count, duration, quorum } => {
+            collection.add_voting(count, duration, quorum)?;
+        },
+        CouncilActionType::EditVoting { new_num, edit_type } => {
+            change = Some(ConfigChange::Voting { new_num, edit_type });
+        },
+        CouncilActionType::RemoveVoting => {
+            collection.remove_voting()?;
+        },
+        CouncilActionType::AddTrade { trade_fee, duration } => {
+            collection.add_trade(trade_fee, duration)?;
+        },
+        CouncilActionType::EditTrade { new_num, edit_type } => {
+            change = Some(ConfigChange::Trade { new_num, edit_type });
+        },
+        CouncilActionType::RemoveTrade => {
+            collection.remove_trade()?;
+        },
+        CouncilActionType::AddStake { emission, max_supply, stake_mode, emission_model } => {
+            collection.add_stake(emission, max_supply, stake_mode, emission_model)?;
+        },
+        CouncilActionType::EditStake { emission } => {
+            change = Some(ConfigChange::Stake { emission });
+        },
+        CouncilActionType::RemoveStake => {
+            collection.remove_stake()?;
+        },
+        CouncilActionType::TopUpEmission { amount } => {
+            collection.top_up_emission(amount)?;
+        },
+        CouncilActionType::SetRarityRoot { rarity_root } => {
+            change = Some(ConfigChange::RarityRoot { rarity_root });
+        },
+        CouncilActionType::SetLockTiers { lock_tiers } => {
+            change = Some(ConfigChange::LockTiers { lock_tiers });
+        },
+        CouncilActionType::SetEmissionSchedule { emission_schedule } => {
+            change = Some(ConfigChange::EmissionSchedule { emission_schedule });
+        },
+        CouncilActionType::SetUnbondingPeriod { unbonding_period } => {
+            change = Some(ConfigChange::UnbondingPeriod { unbonding_period });
+        },
+        CouncilActionType::EditCoReward { index, emission } => {
+            change = Some(ConfigChange::CoReward { index, emission });
+        },
+        CouncilActionType::UpdateTreasury { treasury } => {
+            change = Some(ConfigChange::Treasury { treasury });
+        },
+        CouncilActionType::WithdrawTreasury { destination, amount } => {
+            let (treasury_vault, destination_account) = match (
+                ctx.accounts.treasury_vault.as_ref(),
+                ctx.accounts.destination.as_ref()
+            ) {
+                (Some(treasury_vault), Some(destination_account)) => (treasury_vault, destination_account),
+                _ => {
+                    return Err(Errors::AccountNotProvided.into());
+                }
+            };
+
+            require_keys_eq!(destination_account.key(), destination, Errors::CouncilActionMismatch);
+
+            TreasuryVault::withdraw(&treasury_vault.to_account_info(), destination_account, amount)?;
+        },
+        CouncilActionType::UpdateCouncil { members, threshold } => {
+            Council::validate(&members, threshold)?;
+            council.members = members;
+            council.threshold = threshold;
+        },
+        // Consumed by the instructions they authorize
+        CouncilActionType::CloseCollection |
+        CouncilActionType::CancelPendingChange { .. } |
+        CouncilActionType::SetPause { .. } |
+        CouncilActionType::InitTreasuryVault |
+        CouncilActionType::ProposeAdmin { .. } |
+        CouncilActionType::AddToken { .. } |
+        CouncilActionType::AddRewardVault { .. } |
+        CouncilActionType::FundRewardVault { .. } |
+        CouncilActionType::AddCoReward { .. } |
+        CouncilActionType::AddCoRewardVault { .. } |
+        CouncilActionType::UpdateTokenMetadata { .. } => {
+            return Err(Errors::CouncilActionMismatch.into());
+        }
+    }
+
+    if let Some(change) = change {
+        // Validates the change against the current configuration
+        collection.clone().apply_change(&change)?;
+
+        let collection_key = collection.key();
+        let creator = ctx.accounts.signer.key();
+
+        match ctx.accounts.pending_change.as_mut() {
+            Some(pending_change) => {
+                ***pending_change = PendingChange::new(
+                    collection_key,
+                    change,
+                    Collection::TIMELOCK,
+                    creator
+                );
+            },
+            None => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        }
+    }
+
+    Ok(())
+}
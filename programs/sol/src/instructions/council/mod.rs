@@ -0,0 +1,9 @@
+mod register_council;
+mod create_council_action;
+mod approve_council_action;
+mod execute_council_action;
+
+pub use register_council::*;
+pub use create_council_action::*;
+pub use approve_council_action::*;
+pub use execute_council_action::*;
\ No newline at end of file
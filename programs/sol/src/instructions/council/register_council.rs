@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+use crate::{states::{Collection,Council},Errors};
+
+#[derive(Accounts)]
+pub struct RegisterCouncil<'info> {
+    #[account(
+        mut,
+        seeds = [b"collection", verified_collection.key().as_ref()],
+        bump,
+        constraint = collection_details.admin == owner.key() @ Errors::NotAdmin,
+        constraint = collection_details.council.is_none() @ Errors::CouncilAlreadyRegistered
+    )]
+    pub collection_details: Account<'info, Collection>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Council::LEN,
+        seeds = [b"council", collection_details.key().as_ref()],
+        bump
+    )]
+    pub council: Account<'info, Council>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft
+    )]
+    pub verified_collection: Account<'info, MetadataAccount>,
+
+    pub system_program: Program<'info, System>
+}
+
+pub fn register_council_handler(
+    ctx: Context<RegisterCouncil>,
+    members: Vec<Pubkey>,
+    threshold: u8
+) -> Result<()> {
+    let collection = ctx.accounts.collection_details.key();
+    let council = &mut ctx.accounts.council;
+
+    **council = Council::new(collection, members, threshold)?;
+
+    ctx.accounts.collection_details.council = Some(council.key());
+    Ok(())
+}
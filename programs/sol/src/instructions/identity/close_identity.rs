@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::states::{Identity,Username};
+use crate::Errors;
+
+#[derive(Accounts)]
+pub struct CloseIdentity<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"identity",
+            holder.key().as_ref(),
+            collection.key().as_ref()
+        ],
+        bump,
+        close = holder
+    )]
+    pub identity_details: Account<'info,Identity>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"username",
+            identity_details.username.as_bytes(),
+            collection.key().as_ref()
+        ],
+        bump,
+        constraint = username_details.key == holder.key() @ Errors::InvalidOwner,
+        close = holder
+    )]
+    pub username_details: Account<'info,Username>,
+
+    /// CHECK: Only the key is used (the collection must be closed)
+    pub collection: AccountInfo<'info>,
+
+    /// CHECK: The holder who paid for the identity (seeds of the identity)
+    #[account(mut)]
+    pub holder: AccountInfo<'info>,
+
+    pub signer: Signer<'info>,
+}
+
+/// The identity accounts can be closed by anyone once the collection is closed
+pub fn close_identity_handler(ctx: Context<CloseIdentity>) -> Result<()> {
+    require_eq!(ctx.accounts.collection.data_is_empty(), true, Errors::CollectionNotClosed);
+
+    Ok(())
+}
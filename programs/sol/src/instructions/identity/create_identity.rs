@@ -4,7 +4,7 @@ use anchor_spl::{
     metadata::MetadataAccount
 };
 
-use crate::states::{Collection,Identity,Username};
+use crate::states::{Collection,Identity,Username,PausableOperation};
 use crate::{Errors, MPL_TOKEN_METADATA_ID};
 
 #[derive(Accounts)]
@@ -71,6 +71,8 @@ pub struct CreateIdentity<'info> {
 
 pub fn create_identity_handler(ctx: Context<CreateIdentity>,username: String) -> Result<()> {
 
+    ctx.accounts.collection.check_operation(PausableOperation::CreateIdentity)?;
+
     require_gte!(15, username.len(), Errors::StringLengthExceeds);
 
     let identity_details = &mut ctx.accounts.identity_details;
@@ -1,3 +1,5 @@
 mod create_identity;
+mod close_identity;
 
-pub use create_identity::*;
\ No newline at end of file
+pub use create_identity::*;
+pub use close_identity::*;
\ No newline at end of file
@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::MetadataAccount,
+    token::{
+        self,
+        Mint,
+        Token,
+        SetAuthority,
+        spl_token::instruction::AuthorityType
+    }
+};
+use crate::states::{Collection,CollectionV0,AuthorityBumps};
+use crate::{Errors, ID, utils};
+
+#[derive(Accounts)]
+pub struct MigrateCollection<'info> {
+    /// CHECK: Deserialized in the handler (the original layout can't be loaded as Collection)
+    #[account(
+        mut,
+        owner = ID,
+        seeds = [b"collection", verified_collection.key().as_ref()],
+        bump
+    )]
+    pub collection_details: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        constraint = verified_collection.collection.is_none() @ Errors::NotCollectionNft,
+        constraint = verified_collection.update_authority == owner.key()
+        @ Errors::NotUpdateAuthority
+    )]
+    pub verified_collection: Account<'info, MetadataAccount>,
+
+    #[account(mut)]
+    pub token_mint: Option<Account<'info, Mint>>,
+
+    /// CHECK: The original mint authority PDA (shared by all the collections)
+    #[account(
+        seeds = [b"token-authority"],
+        bump
+    )]
+    pub legacy_token_authority: Option<AccountInfo<'info>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> MigrateCollection<'info> {
+    /// Moves the mint authority of the collection token to the token-authority PDA of the collection
+    pub fn migrate_mint_authority(&self, token_mint: Pubkey) -> Result<()> {
+        let (mint, legacy_token_authority, token_program) = match (
+            self.token_mint.as_ref(),
+            self.legacy_token_authority.as_ref(),
+            self.token_program.as_ref()
+        ) {
+            (Some(mint), Some(legacy_token_authority), Some(token_program)) => {
+                (mint, legacy_token_authority, token_program)
+            },
+            _ => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
+
+        require_keys_eq!(mint.key(), token_mint, Errors::TokenNotFound);
+
+        let (_legacy_authority, legacy_bump) = Pubkey::find_program_address(&[b"token-authority"], &ID);
+        let legacy_seed = &[&b"token-authority"[..], &[legacy_bump]];
+
+        let (token_authority, _) = Pubkey::find_program_address(
+            &[b"token-authority", self.collection_details.key().as_ref()],
+            &ID
+        );
+
+        let cpi_accounts = SetAuthority {
+            account_or_mint: mint.to_account_info(),
+            current_authority: legacy_token_authority.to_account_info()
+        };
+        let cpi_context = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+
+        token::set_authority(
+            cpi_context.with_signer(&[&legacy_seed[..]]),
+            AuthorityType::MintTokens,
+            Some(token_authority)
+        )
+    }
+}
+
+/// * Upgrades the original layout, the update authority becomes the admin of the collection
+/// * only the original layout is handled, the version field is what a later upgrade would match on
+pub fn migrate_collection_handler(ctx: Context<MigrateCollection>) -> Result<()> {
+    let collection_details = &ctx.accounts.collection_details;
+    let data = utils::read_account_data::<Collection>(collection_details)?;
+
+    require_eq!(data.len() + 8, CollectionV0::LEN, Errors::AlreadyMigrated);
+
+    let legacy = CollectionV0::deserialize(&mut &data[..])?;
+
+    if let Some(token_mint) = legacy.token_mint {
+        ctx.accounts.migrate_mint_authority(token_mint)?;
+    }
+
+    let authority_bumps = AuthorityBumps::find(&collection_details.key());
+    let collection = Collection::from_v0(legacy, ctx.accounts.owner.key(), authority_bumps);
+
+    utils::write_migrated_account(
+        collection_details,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Collection::LEN,
+        &collection
+    )
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::states::{Proposal,ProposalV0};
+use crate::{Errors, ID, utils};
+
+#[derive(Accounts)]
+pub struct MigrateProposal<'info> {
+    /// CHECK: Deserialized in the handler (the original layout can't be loaded as Proposal)
+    #[account(
+        mut,
+        owner = ID
+    )]
+    pub proposal_details: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>
+}
+
+pub fn migrate_proposal_handler(ctx: Context<MigrateProposal>) -> Result<()> {
+    let proposal_details = &ctx.accounts.proposal_details;
+    let data = utils::read_account_data::<Proposal>(proposal_details)?;
+
+    // The proposal size is variable, so the original layout is recognised
+    // by deserializing it and comparing the size allocated for it
+    let legacy = ProposalV0::deserialize(&mut &data[..])
+        .map_err(|_| Errors::AlreadyMigrated)?;
+
+    require_eq!(data.len() + 8, legacy.allocated_len(), Errors::AlreadyMigrated);
+
+    let new_len = legacy.allocated_len() + 1;
+    let proposal = Proposal::from_v0(legacy);
+
+    utils::write_migrated_account(
+        proposal_details,
+        &ctx.accounts.signer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+        &proposal
+    )
+}
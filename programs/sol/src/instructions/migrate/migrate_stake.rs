@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{
+    self,
+    TokenAccount,
+    Mint,
+    Token,
+    SetAuthority,
+    spl_token::instruction::AuthorityType
+};
+use crate::states::{Stake,StakeV0,Collection};
+use crate::{Errors, ID, utils};
+
+#[derive(Accounts)]
+pub struct MigrateStake<'info> {
+    /// CHECK: Deserialized in the handler (the original layout can't be loaded as Stake)
+    #[account(
+        mut,
+        owner = ID,
+        seeds = [
+            b"stake",
+            nft_mint.key().as_ref()
+        ],
+        bump
+    )]
+    pub stake_details: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"nft-escrow",
+            nft_mint.key().as_ref()
+        ],
+        bump,
+        token::mint = nft_mint,
+        token::authority = legacy_nft_authority
+    )]
+    pub nft_escrow: Account<'info,TokenAccount>,
+
+    pub nft_mint: Account<'info, Mint>,
+
+    /// The collection must be migrated first
+    #[account(mut)]
+    pub collection: Box<Account<'info, Collection>>,
+
+    /// CHECK: The original owner PDA of the staked NFTs (shared by all the collections)
+    #[account(
+        seeds = [b"nft-authority"],
+        bump
+    )]
+    pub legacy_nft_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>
+}
+
+impl<'info> MigrateStake<'info> {
+    pub fn set_authority_context(&self) -> CpiContext<'_,'_,'_,'info, SetAuthority<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = SetAuthority {
+            account_or_mint: self.nft_escrow.to_account_info(),
+            current_authority: self.legacy_nft_authority.to_account_info()
+        };
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+/// * Also moves the escrowed NFT to the nft-authority PDA of the collection
+/// * the rewards accrued so far are credited to the stake as unclaimed
+pub fn migrate_stake_handler(ctx: Context<MigrateStake>) -> Result<()> {
+    let stake_details = &ctx.accounts.stake_details;
+    let data = utils::read_account_data::<Stake>(stake_details)?;
+
+    require_eq!(data.len() + 8, StakeV0::LEN, Errors::AlreadyMigrated);
+
+    let legacy = StakeV0::deserialize(&mut &data[..])?;
+
+    require_keys_eq!(legacy.collection, ctx.accounts.collection.key(), Errors::CollectionNotSame);
+
+    let (_legacy_authority, legacy_bump) = Pubkey::find_program_address(&[b"nft-authority"], &ID);
+    let legacy_seed = &[&b"nft-authority"[..], &[legacy_bump]];
+
+    let (nft_authority, _) = Pubkey::find_program_address(
+        &[b"nft-authority", legacy.collection.as_ref()],
+        &ID
+    );
+
+    token::set_authority(
+        ctx.accounts.set_authority_context().with_signer(&[&legacy_seed[..]]),
+        AuthorityType::AccountOwner,
+        Some(nft_authority)
+    )?;
+
+    let collection = &mut ctx.accounts.collection;
+    collection.update_reward_index()?;
+
+    // The rewards accrued since the staking (or the last withdrawal) at the current emission
+    let current_time = Clock::get()?.unix_timestamp;
+    let elapsed = current_time.checked_sub(legacy.time).ok_or(Errors::MathOverflow)?;
+    let accrued = utils::calc_reward_index(elapsed, collection.emission, collection.token_decimals)?;
+    let unclaimed_reward = utils::calc_emission(accrued, 0)?;
+
+    let stake = Stake::from_v0(legacy, collection.reward_index, unclaimed_reward);
+
+    // The stakes of the original program weren't counted by the collection
+    collection.add_stakes(1, stake.multiplier as u64)?;
+
+    utils::write_migrated_account(
+        stake_details,
+        &ctx.accounts.signer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Stake::LEN,
+        &stake
+    )
+}
@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{
+    self,
+    TokenAccount,
+    Token,
+    SetAuthority,
+    spl_token::instruction::AuthorityType
+};
+use crate::states::{Trade,TradeV0,Collection};
+use crate::{Errors, ID, utils};
+
+#[derive(Accounts)]
+pub struct MigrateTrade<'info> {
+    /// CHECK: Deserialized in the handler (the original layout can't be loaded as Trade)
+    #[account(
+        mut,
+        owner = ID
+    )]
+    pub trade_details: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        token::authority = legacy_escrow_authority
+    )]
+    pub escrow_party_one: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::authority = legacy_escrow_authority
+    )]
+    pub escrow_party_two: Option<Account<'info, TokenAccount>>,
+
+    /// The collection must be migrated first
+    #[account(mut)]
+    pub collection: Box<Account<'info, Collection>>,
+
+    /// CHECK: The original owner PDA of the trade escrows (shared by all the collections)
+    #[account(
+        seeds = [b"escrow"],
+        bump
+    )]
+    pub legacy_escrow_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>
+}
+
+impl<'info> MigrateTrade<'info> {
+    /// Moves the escrow of the party to the escrow PDA of the collection
+    pub fn migrate_escrow(
+        &self,
+        escrow: Option<&Account<'info, TokenAccount>>,
+        seed: &[u8],
+        legacy: &TradeV0
+    ) -> Result<()> {
+        let escrow = match escrow {
+            Some(escrow) => escrow,
+            None => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
+
+        let (escrow_key, _) = Pubkey::find_program_address(
+            &[
+                seed,
+                legacy.party_one.as_ref(),
+                legacy.party_two.as_ref(),
+                legacy.collection.as_ref()
+            ],
+            &ID
+        );
+        require_keys_eq!(escrow.key(), escrow_key, Errors::IncorrectTokenAccount);
+
+        let (_legacy_authority, legacy_bump) = Pubkey::find_program_address(&[b"escrow"], &ID);
+        let legacy_seed = &[&b"escrow"[..], &[legacy_bump]];
+
+        let (escrow_authority, _) = Pubkey::find_program_address(
+            &[b"escrow", legacy.collection.as_ref()],
+            &ID
+        );
+
+        let cpi_accounts = SetAuthority {
+            account_or_mint: escrow.to_account_info(),
+            current_authority: self.legacy_escrow_authority.to_account_info()
+        };
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+
+        token::set_authority(
+            cpi_context.with_signer(&[&legacy_seed[..]]),
+            AuthorityType::AccountOwner,
+            Some(escrow_authority)
+        )
+    }
+}
+
+/// Also moves the escrows of the trade to the escrow PDA of the collection
+pub fn migrate_trade_handler(ctx: Context<MigrateTrade>) -> Result<()> {
+    let trade_details = &ctx.accounts.trade_details;
+    let data = utils::read_account_data::<Trade>(trade_details)?;
+
+    require_eq!(data.len() + 8, TradeV0::LEN, Errors::AlreadyMigrated);
+
+    let legacy = TradeV0::deserialize(&mut &data[..])?;
+
+    require_keys_eq!(legacy.collection, ctx.accounts.collection.key(), Errors::CollectionNotSame);
+
+    if legacy.spl_amount[0] > 0 {
+        ctx.accounts.migrate_escrow(ctx.accounts.escrow_party_one.as_ref(), b"escrow-one", &legacy)?;
+    }
+
+    if legacy.is_confirmed && legacy.spl_amount[1] > 0 {
+        ctx.accounts.migrate_escrow(ctx.accounts.escrow_party_two.as_ref(), b"escrow-two", &legacy)?;
+    }
+
+    let mut trade = Trade::from_v0(legacy);
+
+    // The accepted trades are charged the current fee on execution
+    if trade.is_confirmed {
+        trade.trade_fee = ctx.accounts.collection.trade_fees;
+    }
+
+    // The trades of the original program weren't counted by the collection
+    ctx.accounts.collection.open_trades += 1;
+
+    utils::write_migrated_account(
+        trade_details,
+        &ctx.accounts.signer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Trade::LEN,
+        &trade
+    )
+}
@@ -0,0 +1,13 @@
+mod migrate_collection;
+mod migrate_stake;
+mod migrate_trade;
+mod migrate_proposal;
+mod release_legacy_stake;
+mod release_legacy_trade;
+
+pub use migrate_collection::*;
+pub use migrate_stake::*;
+pub use migrate_trade::*;
+pub use migrate_proposal::*;
+pub use release_legacy_stake::*;
+pub use release_legacy_trade::*;
\ No newline at end of file
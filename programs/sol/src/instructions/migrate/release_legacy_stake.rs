@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{
+    self,
+    TokenAccount,
+    Mint,
+    Token,
+    Transfer,
+    CloseAccount
+};
+use crate::states::{Stake,StakeV0};
+use crate::{Errors, ID, utils};
+
+#[derive(Accounts)]
+pub struct ReleaseLegacyStake<'info> {
+    /// CHECK: Deserialized in the handler (the original layout can't be loaded as Stake)
+    #[account(
+        mut,
+        owner = ID,
+        seeds = [
+            b"stake",
+            nft_mint.key().as_ref()
+        ],
+        bump
+    )]
+    pub stake_details: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"nft-escrow",
+            nft_mint.key().as_ref()
+        ],
+        bump,
+        token::mint = nft_mint,
+        token::authority = legacy_nft_authority
+    )]
+    pub nft_escrow: Account<'info,TokenAccount>,
+
+    pub nft_mint: Account<'info, Mint>,
+
+    /// Validated against the token account stored in the stake
+    #[account(mut)]
+    pub nft_receive_address: Account<'info, TokenAccount>,
+
+    /// CHECK: Only the key is used (the collection must be closed)
+    pub collection: AccountInfo<'info>,
+
+    /// CHECK: The original owner PDA of the staked NFTs (shared by all the collections)
+    #[account(
+        seeds = [b"nft-authority"],
+        bump
+    )]
+    pub legacy_nft_authority: AccountInfo<'info>,
+
+    /// CHECK: Validated against the owner stored in the stake, receives the rent
+    #[account(mut)]
+    pub owner: AccountInfo<'info>,
+
+    pub signer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>
+}
+
+/// * Returns the escrowed NFT of a stake left in the original layout once its collection is closed
+///   (it can't be migrated anymore), the accrued rewards are forfeited
+/// * can be called by anyone, the rent goes to the owner of the stake
+pub fn release_legacy_stake_handler(ctx: Context<ReleaseLegacyStake>) -> Result<()> {
+    let stake_details = &ctx.accounts.stake_details;
+    let data = utils::read_account_data::<Stake>(stake_details)?;
+
+    require_eq!(data.len() + 8, StakeV0::LEN, Errors::AlreadyMigrated);
+
+    let legacy = StakeV0::deserialize(&mut &data[..])?;
+
+    require_keys_eq!(legacy.collection, ctx.accounts.collection.key(), Errors::CollectionNotSame);
+    require_eq!(ctx.accounts.collection.data_is_empty(), true, Errors::CollectionNotClosed);
+    require_keys_eq!(legacy.owner, ctx.accounts.owner.key(), Errors::InvalidOwner);
+    require_keys_eq!(legacy.nft_send_address, ctx.accounts.nft_receive_address.key(), Errors::IncorrectTokenAccount);
+
+    let (_legacy_authority, legacy_bump) = Pubkey::find_program_address(&[b"nft-authority"], &ID);
+    let legacy_seed = &[&b"nft-authority"[..], &[legacy_bump]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.nft_escrow.to_account_info(),
+        to: ctx.accounts.nft_receive_address.to_account_info(),
+        authority: ctx.accounts.legacy_nft_authority.to_account_info()
+    };
+    let cpi_context = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+
+    token::transfer(cpi_context.with_signer(&[&legacy_seed[..]]), 1)?;
+
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.nft_escrow.to_account_info(),
+        destination: ctx.accounts.owner.to_account_info(),
+        authority: ctx.accounts.legacy_nft_authority.to_account_info()
+    };
+    let cpi_context = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+
+    token::close_account(cpi_context.with_signer(&[&legacy_seed[..]]))?;
+
+    utils::close_legacy_account(stake_details, &ctx.accounts.owner)
+}
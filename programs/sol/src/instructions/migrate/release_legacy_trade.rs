@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{
+    self,
+    TokenAccount,
+    Token,
+    Transfer,
+    CloseAccount
+};
+use crate::states::{Trade,TradeV0};
+use crate::{Errors, ID, utils};
+
+#[derive(Accounts)]
+pub struct ReleaseLegacyTrade<'info> {
+    /// CHECK: Deserialized in the handler (the original layout can't be loaded as Trade)
+    #[account(
+        mut,
+        owner = ID
+    )]
+    pub trade_details: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        token::authority = legacy_escrow_authority
+    )]
+    pub escrow_party_one: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::authority = legacy_escrow_authority
+    )]
+    pub escrow_party_two: Option<Account<'info, TokenAccount>>,
+
+    /// Validated against the token accounts stored in the trade
+    #[account(mut)]
+    pub one_send_address: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub two_send_address: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated against the parties stored in the trade
+    #[account(mut)]
+    pub party_one: AccountInfo<'info>,
+
+    /// CHECK: Validated against the parties stored in the trade
+    #[account(mut)]
+    pub party_two: AccountInfo<'info>,
+
+    /// CHECK: Only the key is used (the collection must be closed)
+    pub collection: AccountInfo<'info>,
+
+    /// CHECK: The original owner PDA of the trade escrows (shared by all the collections)
+    #[account(
+        seeds = [b"escrow"],
+        bump
+    )]
+    pub legacy_escrow_authority: AccountInfo<'info>,
+
+    pub signer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>
+}
+
+impl<'info> ReleaseLegacyTrade<'info> {
+    /// Returns the escrowed tokens of the party and closes its escrow to the party
+    pub fn release_escrow(
+        &self,
+        escrow: Option<&Account<'info, TokenAccount>>,
+        send_address: Option<&Account<'info, TokenAccount>>,
+        party: &AccountInfo<'info>,
+        seed: &[u8],
+        legacy_send_address: Option<Pubkey>,
+        legacy: &TradeV0
+    ) -> Result<()> {
+        let (escrow, send_address) = match (escrow, send_address) {
+            (Some(escrow), Some(send_address)) => (escrow, send_address),
+            _ => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
+
+        let (escrow_key, _) = Pubkey::find_program_address(
+            &[
+                seed,
+                legacy.party_one.as_ref(),
+                legacy.party_two.as_ref(),
+                legacy.collection.as_ref()
+            ],
+            &ID
+        );
+        require_keys_eq!(escrow.key(), escrow_key, Errors::IncorrectTokenAccount);
+        require!(Some(send_address.key()) == legacy_send_address, Errors::IncorrectTokenAccount);
+
+        let (_legacy_authority, legacy_bump) = Pubkey::find_program_address(&[b"escrow"], &ID);
+        let legacy_seed = &[&b"escrow"[..], &[legacy_bump]];
+
+        let cpi_accounts = Transfer {
+            from: escrow.to_account_info(),
+            to: send_address.to_account_info(),
+            authority: self.legacy_escrow_authority.to_account_info()
+        };
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+
+        token::transfer(cpi_context.with_signer(&[&legacy_seed[..]]), escrow.amount)?;
+
+        let cpi_accounts = CloseAccount {
+            account: escrow.to_account_info(),
+            destination: party.clone(),
+            authority: self.legacy_escrow_authority.to_account_info()
+        };
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+
+        token::close_account(cpi_context.with_signer(&[&legacy_seed[..]]))
+    }
+}
+
+/// * Returns the escrowed tokens and SOL of a trade left in the original layout once its collection
+///   is closed (it can't be migrated anymore), as cancel_trade would
+/// * can be called by anyone, the rent goes to the parties who paid it
+pub fn release_legacy_trade_handler(ctx: Context<ReleaseLegacyTrade>) -> Result<()> {
+    let trade_details = &ctx.accounts.trade_details;
+    let data = utils::read_account_data::<Trade>(trade_details)?;
+
+    require_eq!(data.len() + 8, TradeV0::LEN, Errors::AlreadyMigrated);
+
+    let legacy = TradeV0::deserialize(&mut &data[..])?;
+
+    require_keys_eq!(legacy.collection, ctx.accounts.collection.key(), Errors::CollectionNotSame);
+    require_eq!(ctx.accounts.collection.data_is_empty(), true, Errors::CollectionNotClosed);
+    require_keys_eq!(legacy.party_one, ctx.accounts.party_one.key(), Errors::InvalidOwner);
+    require_keys_eq!(legacy.party_two, ctx.accounts.party_two.key(), Errors::InvalidOwner);
+
+    if legacy.spl_amount[0] > 0 {
+        ctx.accounts.release_escrow(
+            ctx.accounts.escrow_party_one.as_ref(),
+            ctx.accounts.one_send_address.as_ref(),
+            &ctx.accounts.party_one,
+            b"escrow-one",
+            legacy.one_send_address,
+            &legacy
+        )?;
+    }
+
+    if legacy.is_confirmed {
+        if legacy.spl_amount[1] > 0 {
+            ctx.accounts.release_escrow(
+                ctx.accounts.escrow_party_two.as_ref(),
+                ctx.accounts.two_send_address.as_ref(),
+                &ctx.accounts.party_two,
+                b"escrow-two",
+                legacy.two_send_address,
+                &legacy
+            )?;
+        }
+
+        if legacy.sol_amount[1] > 0 {
+            **trade_details.try_borrow_mut_lamports()? -= legacy.sol_amount[1];
+            **ctx.accounts.party_two.try_borrow_mut_lamports()? += legacy.sol_amount[1];
+        }
+    }
+
+    // The SOL of the first party is returned with the rent
+    utils::close_legacy_account(trade_details, &ctx.accounts.party_one)
+}
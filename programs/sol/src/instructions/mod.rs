@@ -3,9 +3,13 @@ mod collection;
 mod voting;
 mod stake;
 mod identity;
+mod council;
+mod migrate;
 
 pub use trade::*;
 pub use collection::*;
 pub use voting::*;
 pub use stake::*;
-pub use identity::*;
\ No newline at end of file
+pub use identity::*;
+pub use council::*;
+pub use migrate::*;
\ No newline at end of file
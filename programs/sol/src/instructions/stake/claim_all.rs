@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_interface::{
+        TokenAccount,
+        Mint,
+        TokenInterface
+    },
+    associated_token::AssociatedToken
+};
+
+use crate::states::{Collection,Stake,PausableOperation};
+use crate::{Errors, ID, utils};
+
+#[derive(Accounts)]
+pub struct ClaimAll<'info> {
+    #[account(mut)]
+    pub collection: Box<Account<'info,Collection>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = token_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub token_receive_address: InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = Some(token_mint.key()) == collection.token_mint @ Errors::TokenNotFound
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA used as the mint authority of the collection token
+    #[account(
+        seeds = [b"token-authority", collection.key().as_ref()],
+        bump = collection.token_authority_bump
+    )]
+    pub token_authority: AccountInfo<'info>,
+
+    /// The reward vault of the collection (if the rewards are paid from it)
+    #[account(
+        mut,
+        constraint = Some(reward_vault.key()) == collection.reward_vault @ Errors::RewardVaultNotFound
+    )]
+    pub reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info,System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>
+}
+
+pub fn claim_all_handler<'info>(ctx: Context<'_, '_, '_, 'info, ClaimAll<'info>>) -> Result<()> {
+    ctx.accounts.collection.check_operation(PausableOperation::WithdrawTokens)?;
+
+    let is_stake_active = ctx.accounts.collection.is_staking;
+    require_eq!(is_stake_active,true, Errors::ModuleNotActive);
+
+    // The accounts of the co-rewards follow the stakes
+    let (stake_accounts, co_reward_accounts) = utils::split_co_reward_accounts(
+        ctx.remaining_accounts,
+        &ctx.accounts.collection.co_rewards
+    )?;
+    let stake_count = stake_accounts.len();
+
+    require_gt!(stake_count, 0, Errors::InvalidBatchSize);
+    require_gte!(Stake::MAX_CLAIM_BATCH, stake_count, Errors::InvalidBatchSize);
+
+    // The stakes are only written back at the end, a repeated stake would be claimed again
+    utils::require_unique_keys(stake_accounts.iter().map(|stake_info| stake_info.key))?;
+
+    ctx.accounts.collection.update_reward_index()?;
+
+    let collection_key = ctx.accounts.collection.key();
+    let reward_index = ctx.accounts.collection.reward_index;
+    let owner = ctx.accounts.owner.key();
+
+    let clock = Clock::get().unwrap();
+    let current_time = clock.unix_timestamp;
+
+    let mut reward: u64 = 0;
+    let mut co_rewards = [0u64; Collection::MAX_CO_REWARDS];
+    let mut stakes = Vec::with_capacity(stake_count);
+
+    for stake_info in stake_accounts.iter() {
+        require_eq!(stake_info.is_writable, true, ErrorCode::ConstraintMut);
+
+        // The same checks as the constraints of withdraw_tokens
+        let mut stake_details: Account<Stake> = Account::try_from(stake_info)?;
+        require_keys_eq!(stake_details.collection, collection_key, Errors::CollectionNotSame);
+        require_keys_eq!(stake_details.owner, owner, Errors::InvalidOwner);
+        stake_details.check_claim()?;
+
+        reward = reward
+            .checked_add(stake_details.claim_reward(reward_index)?)
+            .ok_or(Errors::MathOverflow)?;
+
+        let stake_co_rewards = stake_details.claim_co_rewards(&ctx.accounts.collection.co_rewards)?;
+        for (co_reward, stake_co_reward) in co_rewards.iter_mut().zip(stake_co_rewards) {
+            *co_reward = co_reward
+                .checked_add(stake_co_reward)
+                .ok_or(Errors::MathOverflow)?;
+        }
+
+        stake_details.time = current_time;
+        stakes.push(stake_details);
+    }
+
+    let supply = ctx.accounts.token_mint.supply;
+    let vault_balance = ctx.accounts.reward_vault.as_ref().map(|reward_vault| reward_vault.amount);
+    let available = ctx.accounts.collection.reward_available(supply, vault_balance)?;
+    let payout = ctx.accounts.collection.claim_emission(reward, available);
+
+    let token_bump = ctx.accounts.collection.token_authority_bump;
+    let token_seed = &[&b"token-authority"[..], collection_key.as_ref(), &[token_bump]];
+
+    // The rewards of all the stakes are paid at once
+    utils::pay_reward(
+        payout,
+        &ctx.accounts.token_mint,
+        &ctx.accounts.token_receive_address,
+        ctx.accounts.reward_vault.as_deref(),
+        &ctx.accounts.token_authority,
+        &ctx.accounts.token_program.to_account_info(),
+        &token_seed[..]
+    )?;
+
+    let token_authority = ctx.accounts.token_authority.to_account_info();
+    let co_payouts = utils::pay_co_rewards(
+        &mut ctx.accounts.collection.co_rewards,
+        &co_rewards,
+        co_reward_accounts,
+        &owner,
+        &token_authority,
+        &token_seed[..]
+    )?;
+
+    // Fails if nothing is left of any of the accrued rewards
+    if reward > 0 || co_rewards.iter().any(|co_reward| *co_reward > 0) {
+        require!(payout > 0 || co_payouts.iter().any(|co_payout| *co_payout > 0), Errors::EmissionBudgetExhausted);
+    }
+
+    // The rest of a partial payout is kept in the first stake (all of them are of the owner)
+    let unpaid_co_rewards = utils::unpaid_rewards(&co_rewards, &co_payouts);
+    stakes[0].keep_unpaid(reward - payout, &unpaid_co_rewards)?;
+
+    for stake_details in stakes.iter() {
+        stake_details.exit(&ID)?;
+    }
+
+    Ok(())
+}
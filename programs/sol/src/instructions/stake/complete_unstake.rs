@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::states::PausableOperation;
+use crate::utils;
+use super::UnstakeNft;
+
+/// * Returns the unbonding NFT once its unbonding period has passed (the rewards were paid on unstake)
+/// * pays the rest kept on unstake if the payout ran short (forfeited only if the holder opts to)
+pub fn complete_unstake_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, UnstakeNft<'info>>,
+    forfeit_unpaid: bool
+) -> Result<()> {
+    ctx.accounts.collection.check_operation(PausableOperation::UnstakeNft)?;
+
+    ctx.accounts.stake_details.check_complete_unstake()?;
+
+    let (reward, co_rewards) = ctx.accounts.stake_details.take_unclaimed();
+    let (unpaid_reward, unpaid_co_rewards) = ctx.accounts.pay_rewards(reward, &co_rewards, ctx.remaining_accounts)?;
+    utils::require_paid(unpaid_reward, &unpaid_co_rewards, forfeit_unpaid)?;
+
+    ctx.accounts.return_nft()?;
+
+    ctx.accounts.collection.remove_stakes(1, 0)?;
+    ctx.accounts.stake_details.close(ctx.accounts.owner.to_account_info())
+}
@@ -1,7 +1,15 @@
 mod stake_nft;
 mod unstake_nft;
+mod complete_unstake;
 mod withdraw_tokens;
+mod stake_many;
+mod unstake_many;
+mod claim_all;
 
 pub use stake_nft::*;
 pub use unstake_nft::*;
-pub use withdraw_tokens::*;
\ No newline at end of file
+pub use complete_unstake::*;
+pub use withdraw_tokens::*;
+pub use stake_many::*;
+pub use unstake_many::*;
+pub use claim_all::*;
\ No newline at end of file
@@ -0,0 +1,237 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{TokenAccount,Mint,Token,Transfer,Approve,InitializeAccount3,self},
+    metadata::{
+        self as token_metadata,
+        Metadata,
+        FreezeDelegatedAccount
+    }
+};
+use mpl_token_metadata::state::TokenStandard;
+
+use crate::states::{Collection,Stake,StakeMode,PausableOperation};
+use super::RarityProof;
+use crate::{Errors, MPL_TOKEN_METADATA_ID, ID, utils};
+
+/// The accounts of each NFT in the remaining accounts:
+/// mint, token account, metadata, stake PDA and the nft-escrow PDA (escrow mode) or the master edition (freeze mode)
+const ACCOUNTS_PER_NFT: usize = 5;
+
+#[derive(Accounts)]
+pub struct StakeMany<'info> {
+    #[account(mut)]
+    pub collection_details: Box<Account<'info,Collection>>,
+
+    /// CHECK: PDA which owns the escrows or becomes the delegate of the frozen NFTs
+    #[account(
+        seeds = [b"nft-authority", collection_details.key().as_ref()],
+        bump = collection_details.nft_authority_bump
+    )]
+    pub nft_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    pub system_program: Program<'info,System>,
+    pub token_program: Program<'info, Token>,
+    pub metadata_program: Option<Program<'info, Metadata>>
+}
+
+impl<'info> StakeMany<'info> {
+    /// Creates the escrow of the NFT (owned by the nft-authority PDA) and moves the NFT into it
+    pub fn escrow_nft(
+        &self,
+        mint: &AccountInfo<'info>,
+        nft_send_address: &AccountInfo<'info>,
+        nft_escrow: &AccountInfo<'info>
+    ) -> Result<()> {
+        let (escrow_pda, escrow_bump) = Pubkey::find_program_address(
+            &[b"nft-escrow", mint.key.as_ref()],
+            &ID
+        );
+        require_keys_eq!(nft_escrow.key(), escrow_pda, Errors::WrongStakeAccount);
+
+        let escrow_seed = &[&b"nft-escrow"[..], mint.key.as_ref(), &[escrow_bump]];
+
+        utils::create_pda_account(
+            nft_escrow,
+            &self.signer.to_account_info(),
+            &self.system_program.to_account_info(),
+            TokenAccount::LEN,
+            self.token_program.key,
+            &escrow_seed[..]
+        )?;
+
+        let cpi_accounts = InitializeAccount3 {
+            account: nft_escrow.clone(),
+            mint: mint.clone(),
+            authority: self.nft_authority.to_account_info()
+        };
+        token::initialize_account3(CpiContext::new(self.token_program.to_account_info(), cpi_accounts))?;
+
+        let cpi_accounts = Transfer {
+            from: nft_send_address.clone(),
+            to: nft_escrow.clone(),
+            authority: self.signer.to_account_info()
+        };
+        token::transfer(CpiContext::new(self.token_program.to_account_info(), cpi_accounts), 1)
+    }
+
+    /// Delegates the NFT to the nft-authority PDA, which freezes it in the token account of the holder
+    pub fn freeze_nft(
+        &self,
+        mint: &AccountInfo<'info>,
+        nft_send_address: &AccountInfo<'info>,
+        metadata: &AccountInfo<'info>,
+        edition: &AccountInfo<'info>,
+        nft_seed: &[&[u8]]
+    ) -> Result<()> {
+        let metadata_program = match self.metadata_program.as_ref() {
+            Some(metadata_program) => metadata_program,
+            None => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
+
+        let (edition_pda, _) = Pubkey::find_program_address(
+            &[b"metadata", MPL_TOKEN_METADATA_ID.as_ref(), mint.key.as_ref(), b"edition"],
+            &MPL_TOKEN_METADATA_ID
+        );
+        require_keys_eq!(edition.key(), edition_pda, Errors::WrongStakeAccount);
+
+        let cpi_accounts = Approve {
+            to: nft_send_address.clone(),
+            delegate: self.nft_authority.to_account_info(),
+            authority: self.signer.to_account_info()
+        };
+        token::approve(CpiContext::new(self.token_program.to_account_info(), cpi_accounts), 1)?;
+
+        let cpi_accounts = FreezeDelegatedAccount {
+            metadata: metadata.clone(),
+            delegate: self.nft_authority.to_account_info(),
+            token_account: nft_send_address.clone(),
+            edition: edition.clone(),
+            mint: mint.clone(),
+            token_program: self.token_program.to_account_info()
+        };
+        let cpi_context = CpiContext::new(metadata_program.to_account_info(), cpi_accounts);
+
+        token_metadata::freeze_delegated_account(cpi_context.with_signer(&[nft_seed]))
+    }
+}
+
+pub fn stake_many_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, StakeMany<'info>>,
+    rarities: Vec<RarityProof>,
+    lock_tier: Option<u8>
+) -> Result<()> {
+    ctx.accounts.collection_details.check_operation(PausableOperation::StakeNft)?;
+
+    let is_stake_active = ctx.accounts.collection_details.is_staking;
+    require_eq!(is_stake_active,true, Errors::ModuleNotActive);
+
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+    let nft_count = remaining_accounts.len() / ACCOUNTS_PER_NFT;
+
+    require_eq!(remaining_accounts.len() % ACCOUNTS_PER_NFT, 0, Errors::InvalidBatchSize);
+    require_gt!(nft_count, 0, Errors::InvalidBatchSize);
+    require_gte!(Stake::MAX_BATCH, nft_count, Errors::InvalidBatchSize);
+
+    if ctx.accounts.collection_details.rarity_root.is_some() {
+        require_eq!(rarities.len(), nft_count, Errors::InvalidRarityProof);
+    }
+
+    ctx.accounts.collection_details.update_reward_index()?;
+
+    let collection_details = &ctx.accounts.collection_details;
+    let collection_key = collection_details.key();
+    let verified_key = collection_details.verified_collection_key;
+    let reward_index = collection_details.reward_index;
+    let co_reward_indexes = collection_details.co_reward_indexes();
+    let stake_mode = collection_details.stake_mode;
+    let staker = ctx.accounts.signer.key();
+
+    let tier = match lock_tier {
+        Some(lock_tier) => Some(collection_details.lock_tier(lock_tier)?),
+        None => None
+    };
+
+    let nft_bump = collection_details.nft_authority_bump;
+    let nft_seed = &[&b"nft-authority"[..], collection_key.as_ref(), &[nft_bump]];
+
+    let mut staked_weight: u64 = 0;
+
+    for i in 0 .. nft_count {
+        let mint = next_account_info(remaining_accounts)?;
+        let nft_send_address = next_account_info(remaining_accounts)?;
+        let metadata = next_account_info(remaining_accounts)?;
+        let stake_details = next_account_info(remaining_accounts)?;
+        let escrow_or_edition = next_account_info(remaining_accounts)?;
+
+        // The same checks as the constraints of stake_nft
+        let mint_account: Account<Mint> = Account::try_from(mint)?;
+        require_eq!(mint_account.decimals, 0, ErrorCode::ConstraintMintDecimals);
+
+        let token_mint = utils::validate_token_account(nft_send_address, &staker)?;
+        require_keys_eq!(token_mint, mint.key(), ErrorCode::ConstraintTokenMint);
+
+        let metadata_account = utils::verify_metadata_account(mint.key, metadata, &verified_key)?;
+
+        let multiplier = ctx.accounts.collection_details.rarity_multiplier(mint.key, rarities.get(i))?;
+
+        // The programmable NFTs need the accounts of the token metadata program (see stake_nft)
+        require!(
+            metadata_account.token_standard != Some(TokenStandard::ProgrammableNonFungible),
+            Errors::ProgrammableNftNotSupported
+        );
+
+        match stake_mode {
+            StakeMode::Escrow => ctx.accounts.escrow_nft(mint, nft_send_address, escrow_or_edition)?,
+            StakeMode::Freeze => {
+                ctx.accounts.freeze_nft(mint, nft_send_address, metadata, escrow_or_edition, &nft_seed[..])?
+            },
+            StakeMode::Lock => {
+                return Err(Errors::ProgrammableNftNotSupported.into());
+            }
+        }
+
+        let (stake_pda, stake_bump) = Pubkey::find_program_address(
+            &[b"stake", mint.key.as_ref()],
+            &ID
+        );
+        require_keys_eq!(stake_details.key(), stake_pda, Errors::WrongStakeAccount);
+
+        let stake_seed = &[&b"stake"[..], mint.key.as_ref(), &[stake_bump]];
+
+        utils::create_pda_account(
+            stake_details,
+            &ctx.accounts.signer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            Stake::LEN,
+            &ID,
+            &stake_seed[..]
+        )?;
+
+        let mut stake = Stake::new(
+            staker,
+            nft_send_address.key(),
+            collection_key,
+            reward_index,
+            co_reward_indexes,
+            stake_mode,
+            multiplier
+        );
+
+        if let (Some(lock_tier), Some(tier)) = (lock_tier, tier.as_ref()) {
+            stake.lock(lock_tier, tier)?;
+        }
+
+        staked_weight += stake.multiplier as u64;
+
+        stake.try_serialize(&mut &mut stake_details.try_borrow_mut_data()?[..])?;
+    }
+
+    ctx.accounts.collection_details.add_stakes(nft_count as u64, staked_weight)?;
+
+    Ok(())
+}
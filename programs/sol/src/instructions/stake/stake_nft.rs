@@ -1,12 +1,20 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    token::{TokenAccount,Mint,Token,Transfer,SetAuthority,
+    token::{TokenAccount,Mint,Token,Transfer,SetAuthority,Approve,
         spl_token::instruction::AuthorityType, self
     },
-    metadata::MetadataAccount
+    metadata::{
+        self as token_metadata,
+        Metadata,
+        MetadataAccount,
+        FreezeDelegatedAccount
+    }
 };
+use mpl_token_metadata::state::TokenStandard;
+use anchor_lang::solana_program::sysvar;
 
-use crate::states::{Collection,Stake};
+use crate::states::{Collection,Stake,StakeMode,PausableOperation};
+use crate::utils::ProgrammableNft;
 use crate::{Errors, MPL_TOKEN_METADATA_ID, ID};
 
 #[derive(Accounts)]
@@ -34,8 +42,9 @@ pub struct StakeNft<'info> {
         token::mint = mint,
         token::authority = signer
     )]
-    pub nft_escrow: Account<'info,TokenAccount>,
+    pub nft_escrow: Option<Account<'info,TokenAccount>>,
 
+    #[account(mut)]
     pub collection_details: Box<Account<'info,Collection>>,
 
     #[account(
@@ -50,6 +59,7 @@ pub struct StakeNft<'info> {
     pub mint: Account<'info, Mint>,
     
     #[account(
+        mut,
         seeds = [
             b"metadata", 
             MPL_TOKEN_METADATA_ID.as_ref(), 
@@ -62,37 +72,221 @@ pub struct StakeNft<'info> {
         collection_details.verified_collection_key @ Errors::CollectionNotSame
 
     )]
-    pub metadata: Account<'info, MetadataAccount>,
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+
+    /// CHECK: PDA which becomes the delegate of the frozen NFT
+    #[account(
+        seeds = [b"nft-authority", collection_details.key().as_ref()],
+        bump = collection_details.nft_authority_bump
+    )]
+    pub nft_authority: Option<AccountInfo<'info>>,
+
+    /// CHECK: The master edition of the NFT (the freeze authority of the mint)
+    #[account(
+        seeds = [
+            b"metadata", 
+            MPL_TOKEN_METADATA_ID.as_ref(), 
+            mint.key().as_ref(),
+            b"edition"
+        ],
+        seeds::program = MPL_TOKEN_METADATA_ID,
+        bump
+    )]
+    pub edition: Option<AccountInfo<'info>>,
+
+    /// CHECK: The token record of the pNFT (validated by the token metadata program)
+    #[account(
+        mut,
+        seeds = [
+            b"metadata", 
+            MPL_TOKEN_METADATA_ID.as_ref(), 
+            mint.key().as_ref(),
+            b"token_record",
+            nft_send_address.key().as_ref()
+        ],
+        seeds::program = MPL_TOKEN_METADATA_ID,
+        bump
+    )]
+    pub token_record: Option<AccountInfo<'info>>,
+
+    /// CHECK: The rule set of the pNFT (validated by the token metadata program)
+    pub authorization_rules: Option<AccountInfo<'info>>,
+
+    /// CHECK: The program of the rule set (validated by the token metadata program)
+    pub authorization_rules_program: Option<AccountInfo<'info>>,
+
+    /// CHECK: The instructions sysvar (required by the token metadata program for the pNFTs)
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<AccountInfo<'info>>,
 
     #[account(mut)]
     pub signer: Signer<'info>,
 
     pub system_program: Program<'info,System>,
-    pub token_program: Program<'info, Token>
+    pub token_program: Program<'info, Token>,
+    pub metadata_program: Option<Program<'info, Metadata>>
 }
 
 impl<'info> StakeNft<'info> {
-    pub fn transfer_nft_context(&self) -> CpiContext<'_,'_,'_,'info, Transfer<'info>> {
+    pub fn transfer_nft_context(
+        &self,
+        nft_escrow: &Account<'info,TokenAccount>
+    ) -> CpiContext<'_,'_,'_,'info, Transfer<'info>> {
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = Transfer {
             from: self.nft_send_address.to_account_info(),
-            to: self.nft_escrow.to_account_info(),
+            to: nft_escrow.to_account_info(),
             authority: self.signer.to_account_info()
         };
         CpiContext::new(cpi_program, cpi_accounts)
     }
 
-    pub fn set_authority_context(&self) -> CpiContext<'_,'_,'_,'info, SetAuthority<'info>> {
+    pub fn set_authority_context(
+        &self,
+        nft_escrow: &Account<'info,TokenAccount>
+    ) -> CpiContext<'_,'_,'_,'info, SetAuthority<'info>> {
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = SetAuthority {
-            account_or_mint: self.nft_escrow.to_account_info(),
+            account_or_mint: nft_escrow.to_account_info(),
             current_authority: self.signer.to_account_info()
         };
         CpiContext::new(cpi_program, cpi_accounts)
     }
+
+    /// Moves the NFT to the escrow and hands the escrow over to the nft-authority PDA
+    pub fn escrow_nft(&self) -> Result<()> {
+        let nft_escrow = match self.nft_escrow.as_ref() {
+            Some(nft_escrow) => nft_escrow,
+            None => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
+
+        token::transfer(self.transfer_nft_context(nft_escrow), 1)?;
+
+        let (nft_authority, _) = Pubkey::find_program_address(
+            &[b"nft-authority", self.collection_details.key().as_ref()],
+            &ID
+        );
+
+        token::set_authority(
+            self.set_authority_context(nft_escrow), 
+            AuthorityType::AccountOwner, 
+            Some(nft_authority)
+        )
+    }
+
+    /// Delegates the NFT to the nft-authority PDA, which freezes it in the token account of the holder
+    pub fn freeze_nft(&self) -> Result<()> {
+        if self.nft_escrow.is_some() {
+            return Err(Errors::AccountNotRequired.into());
+        }
+
+        let (nft_authority, edition, metadata_program) = match (
+            self.nft_authority.as_ref(),
+            self.edition.as_ref(),
+            self.metadata_program.as_ref()
+        ) {
+            (Some(nft_authority), Some(edition), Some(metadata_program)) => {
+                (nft_authority, edition, metadata_program)
+            },
+            _ => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
+
+        let cpi_accounts = Approve {
+            to: self.nft_send_address.to_account_info(),
+            delegate: nft_authority.to_account_info(),
+            authority: self.signer.to_account_info()
+        };
+        token::approve(CpiContext::new(self.token_program.to_account_info(), cpi_accounts), 1)?;
+
+        let collection_key = self.collection_details.key();
+        let nft_bump = self.collection_details.nft_authority_bump;
+        let nft_seed = &[&b"nft-authority"[..], collection_key.as_ref(), &[nft_bump]];
+
+        let cpi_accounts = FreezeDelegatedAccount {
+            metadata: self.metadata.to_account_info(),
+            delegate: nft_authority.to_account_info(),
+            token_account: self.nft_send_address.to_account_info(),
+            edition: edition.to_account_info(),
+            mint: self.mint.to_account_info(),
+            token_program: self.token_program.to_account_info()
+        };
+        let cpi_context = CpiContext::new(metadata_program.to_account_info(), cpi_accounts);
+
+        token_metadata::freeze_delegated_account(cpi_context.with_signer(&[&nft_seed[..]]))
+    }
+
+    /// Approves the nft-authority PDA as the staking delegate of the pNFT, which locks it in the token account of the holder
+    pub fn lock_nft(&self) -> Result<()> {
+        if self.nft_escrow.is_some() {
+            return Err(Errors::AccountNotRequired.into());
+        }
+
+        let (nft_authority, edition, token_record, sysvar_instructions, metadata_program) = match (
+            self.nft_authority.as_ref(),
+            self.edition.as_ref(),
+            self.token_record.as_ref(),
+            self.sysvar_instructions.as_ref(),
+            self.metadata_program.as_ref()
+        ) {
+            (
+                Some(nft_authority),
+                Some(edition),
+                Some(token_record),
+                Some(sysvar_instructions),
+                Some(metadata_program)
+            ) => {
+                (nft_authority, edition, token_record, sysvar_instructions, metadata_program)
+            },
+            _ => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
+
+        let metadata = self.metadata.to_account_info();
+        let mint = self.mint.to_account_info();
+        let token = self.nft_send_address.to_account_info();
+        let owner = self.signer.to_account_info();
+        let system_program = self.system_program.to_account_info();
+        let token_program = self.token_program.to_account_info();
+        let metadata_program = metadata_program.to_account_info();
+
+        let programmable_nft = ProgrammableNft {
+            metadata: &metadata,
+            edition,
+            token_record,
+            mint: &mint,
+            token: &token,
+            owner: &owner,
+            delegate: nft_authority,
+            system_program: &system_program,
+            sysvar_instructions,
+            token_program: &token_program,
+            metadata_program: &metadata_program,
+            authorization_rules_program: self.authorization_rules_program.as_ref(),
+            authorization_rules: self.authorization_rules.as_ref()
+        };
+
+        programmable_nft.delegate()?;
+
+        let collection_key = self.collection_details.key();
+        let nft_bump = self.collection_details.nft_authority_bump;
+        let nft_seed = &[&b"nft-authority"[..], collection_key.as_ref(), &[nft_bump]];
+
+        programmable_nft.lock(&[&nft_seed[..]])
+    }
 }
 
-pub fn stake_nft_handler(ctx: Context<StakeNft>) -> Result<()> {
+pub fn stake_nft_handler(
+    ctx: Context<StakeNft>,
+    rarity: Option<RarityProof>,
+    lock_tier: Option<u8>
+) -> Result<()> {
+    ctx.accounts.collection_details.check_operation(PausableOperation::StakeNft)?;
+
     let collection_details = &ctx.accounts.collection_details;
     let nft_send_address = ctx.accounts.nft_send_address.key();
     let staker = ctx.accounts.signer.key();
@@ -100,23 +294,54 @@ pub fn stake_nft_handler(ctx: Context<StakeNft>) -> Result<()> {
     let is_stake_active = collection_details.is_staking;
     require_eq!(is_stake_active,true, Errors::ModuleNotActive);
 
-    token::transfer(ctx.accounts.transfer_nft_context(), 1)?;
+    let multiplier = collection_details.rarity_multiplier(
+        &ctx.accounts.mint.key(),
+        rarity.as_ref()
+    )?;
+
+    // The programmable NFTs can't be moved or frozen with the token program
+    let stake_mode = if ctx.accounts.metadata.token_standard ==
+    Some(TokenStandard::ProgrammableNonFungible) {
+        StakeMode::Lock
+    } else {
+        collection_details.stake_mode
+    };
 
-    let (nft_authority, _) = Pubkey::find_program_address(&[b"nft-authority"], &ID);
+    match stake_mode {
+        StakeMode::Escrow => ctx.accounts.escrow_nft()?,
+        StakeMode::Freeze => ctx.accounts.freeze_nft()?,
+        StakeMode::Lock => ctx.accounts.lock_nft()?
+    }
 
-    token::set_authority(
-        ctx.accounts.set_authority_context(), 
-        AuthorityType::AccountOwner, 
-        Some(nft_authority)
-    )?;
+    let collection_details = &mut ctx.accounts.collection_details;
+    collection_details.update_reward_index()?;
 
     let stake_details = &mut ctx.accounts.stake_details;
 
     **stake_details = Stake::new(
         staker,
         nft_send_address,
-        collection_details.key()
+        collection_details.key(),
+        collection_details.reward_index,
+        collection_details.co_reward_indexes(),
+        stake_mode,
+        multiplier
     );
 
+    if let Some(lock_tier) = lock_tier {
+        let tier = collection_details.lock_tier(lock_tier)?;
+        stake_details.lock(lock_tier, &tier)?;
+    }
+
+    collection_details.add_stakes(1, stake_details.multiplier as u64)?;
+
     Ok(())
 }
+
+#[derive(AnchorSerialize,AnchorDeserialize)]
+pub struct RarityProof {
+    /// The rarity multiplier of the NFT (in percent of the emission)
+    pub multiplier: u16,
+    /// The sibling hashes from the leaf of the NFT up to the rarity root
+    pub proof: Vec<[u8; 32]>
+}
@@ -0,0 +1,305 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{Token,Transfer,Revoke,CloseAccount,self},
+    token_interface::{
+        TokenAccount as RewardTokenAccount,
+        Mint as RewardMint,
+        TokenInterface
+    },
+    metadata::{
+        self as token_metadata,
+        Metadata,
+        ThawDelegatedAccount
+    },
+    associated_token::AssociatedToken
+};
+
+use crate::states::{Collection,Stake,StakeMode,PausableOperation};
+use crate::{Errors, MPL_TOKEN_METADATA_ID, ID, utils};
+
+/// The accounts of each NFT in the remaining accounts:
+/// mint, token account, stake PDA, the nft-escrow PDA (escrow mode) or the master edition (freeze mode)
+/// and the metadata PDA (only used in freeze mode)
+const ACCOUNTS_PER_NFT: usize = 5;
+
+#[derive(Accounts)]
+pub struct UnstakeMany<'info> {
+    #[account(mut)]
+    pub collection: Box<Account<'info,Collection>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = token_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = reward_token_program
+    )]
+    pub token_receive_address: InterfaceAccount<'info,RewardTokenAccount>,
+
+    #[account(
+        mut,
+        constraint = Some(token_mint.key()) == collection.token_mint @ Errors::TokenNotFound
+    )]
+    pub token_mint: InterfaceAccount<'info, RewardMint>,
+
+    /// CHECK: PDA used as the mint authority of the collection token
+    #[account(
+        seeds = [b"token-authority", collection.key().as_ref()],
+        bump = collection.token_authority_bump
+    )]
+    pub token_authority: AccountInfo<'info>,
+
+    /// The reward vault of the collection (if the rewards are paid from it)
+    #[account(
+        mut,
+        constraint = Some(reward_vault.key()) == collection.reward_vault @ Errors::RewardVaultNotFound
+    )]
+    pub reward_vault: Option<Box<InterfaceAccount<'info, RewardTokenAccount>>>,
+
+    /// CHECK: PDA which owns the escrows or is the delegate of the frozen NFTs
+    #[account(
+        seeds = [b"nft-authority", collection.key().as_ref()],
+        bump = collection.nft_authority_bump
+    )]
+    pub nft_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info,System>,
+    pub token_program: Program<'info, Token>,
+    /// The program of the collection token (Token or Token-2022)
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Option<Program<'info, Metadata>>
+}
+
+impl<'info> UnstakeMany<'info> {
+    /// Returns the NFT from the escrow and closes the escrow
+    pub fn release_escrow(
+        &self,
+        mint: &AccountInfo<'info>,
+        nft_send_address: &AccountInfo<'info>,
+        nft_escrow: &AccountInfo<'info>,
+        nft_seed: &[&[u8]]
+    ) -> Result<()> {
+        let (escrow_pda, _) = Pubkey::find_program_address(
+            &[b"nft-escrow", mint.key.as_ref()],
+            &ID
+        );
+        require_keys_eq!(nft_escrow.key(), escrow_pda, Errors::WrongStakeAccount);
+
+        let cpi_accounts = Transfer {
+            from: nft_escrow.clone(),
+            to: nft_send_address.clone(),
+            authority: self.nft_authority.to_account_info()
+        };
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_context.with_signer(&[nft_seed]), 1)?;
+
+        let cpi_accounts = CloseAccount {
+            account: nft_escrow.clone(),
+            destination: self.owner.to_account_info(),
+            authority: self.nft_authority.to_account_info()
+        };
+        let cpi_context = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        token::close_account(cpi_context.with_signer(&[nft_seed]))
+    }
+
+    /// Thaws the NFT in the token account of the owner and revokes the delegate
+    pub fn thaw_nft(
+        &self,
+        mint: &AccountInfo<'info>,
+        nft_send_address: &AccountInfo<'info>,
+        edition: &AccountInfo<'info>,
+        metadata: &AccountInfo<'info>,
+        nft_seed: &[&[u8]]
+    ) -> Result<()> {
+        let metadata_program = match self.metadata_program.as_ref() {
+            Some(metadata_program) => metadata_program,
+            None => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
+
+        let (edition_pda, _) = Pubkey::find_program_address(
+            &[b"metadata", MPL_TOKEN_METADATA_ID.as_ref(), mint.key.as_ref(), b"edition"],
+            &MPL_TOKEN_METADATA_ID
+        );
+        require_keys_eq!(edition.key(), edition_pda, Errors::WrongStakeAccount);
+
+        let (metadata_pda, _) = Pubkey::find_program_address(
+            &[b"metadata", MPL_TOKEN_METADATA_ID.as_ref(), mint.key.as_ref()],
+            &MPL_TOKEN_METADATA_ID
+        );
+        require_keys_eq!(metadata.key(), metadata_pda, Errors::WrongStakeAccount);
+
+        let cpi_accounts = ThawDelegatedAccount {
+            metadata: metadata.clone(),
+            delegate: self.nft_authority.to_account_info(),
+            token_account: nft_send_address.clone(),
+            edition: edition.clone(),
+            mint: mint.clone(),
+            token_program: self.token_program.to_account_info()
+        };
+        let cpi_context = CpiContext::new(metadata_program.to_account_info(), cpi_accounts);
+
+        token_metadata::thaw_delegated_account(cpi_context.with_signer(&[nft_seed]))?;
+
+        let cpi_accounts = Revoke {
+            source: nft_send_address.clone(),
+            authority: self.owner.to_account_info()
+        };
+        token::revoke(CpiContext::new(self.token_program.to_account_info(), cpi_accounts))
+    }
+}
+
+pub fn unstake_many_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, UnstakeMany<'info>>,
+    forfeit_unpaid: bool
+) -> Result<()> {
+    ctx.accounts.collection.check_operation(PausableOperation::UnstakeNft)?;
+
+    // The accounts of the co-rewards follow the NFTs
+    let (nft_accounts, co_reward_accounts) = utils::split_co_reward_accounts(
+        ctx.remaining_accounts,
+        &ctx.accounts.collection.co_rewards
+    )?;
+
+    let remaining_accounts = &mut nft_accounts.iter();
+    let nft_count = remaining_accounts.len() / ACCOUNTS_PER_NFT;
+
+    require_eq!(remaining_accounts.len() % ACCOUNTS_PER_NFT, 0, Errors::InvalidBatchSize);
+    require_gt!(nft_count, 0, Errors::InvalidBatchSize);
+    require_gte!(Stake::MAX_BATCH, nft_count, Errors::InvalidBatchSize);
+
+    // A stake passed twice would be paid twice
+    utils::require_unique_keys(nft_accounts.chunks(ACCOUNTS_PER_NFT).map(|accounts| accounts[2].key))?;
+
+    ctx.accounts.collection.update_reward_index()?;
+
+    let collection_key = ctx.accounts.collection.key();
+    let reward_index = ctx.accounts.collection.reward_index;
+    let owner = ctx.accounts.owner.key();
+    let unbonding_period = ctx.accounts.collection.unbonding_period;
+
+    let nft_bump = ctx.accounts.collection.nft_authority_bump;
+    let nft_seed = &[&b"nft-authority"[..], collection_key.as_ref(), &[nft_bump]];
+
+    let mut reward: u64 = 0;
+    let mut co_rewards = [0u64; Collection::MAX_CO_REWARDS];
+    let mut staked_weight: u64 = 0;
+    let mut returned: u64 = 0;
+    let mut unbonding_stakes: Vec<Account<Stake>> = Vec::new();
+
+    for _i in 0 .. nft_count {
+        let mint = next_account_info(remaining_accounts)?;
+        let nft_send_address = next_account_info(remaining_accounts)?;
+        let stake_info = next_account_info(remaining_accounts)?;
+        let escrow_or_edition = next_account_info(remaining_accounts)?;
+        let metadata = next_account_info(remaining_accounts)?;
+
+        let (stake_pda, _) = Pubkey::find_program_address(
+            &[b"stake", mint.key.as_ref()],
+            &ID
+        );
+        require_keys_eq!(stake_info.key(), stake_pda, Errors::WrongStakeAccount);
+
+        // The same checks as the constraints of unstake_nft
+        let mut stake_details: Account<Stake> = Account::try_from(stake_info)?;
+        require_keys_eq!(stake_details.collection, collection_key, Errors::CollectionNotSame);
+        require_keys_eq!(stake_details.owner, owner, Errors::InvalidOwner);
+        require_keys_eq!(stake_details.nft_send_address, nft_send_address.key(), Errors::IncorrectTokenAccount);
+
+        let is_early_exit = stake_details.check_unstake()?;
+
+        let stake_reward = stake_details.claim_reward(reward_index)?;
+        let stake_co_rewards = stake_details.claim_co_rewards(&ctx.accounts.collection.co_rewards)?;
+
+        // The early exit forfeits the accrued rewards
+        if !is_early_exit {
+            reward = reward
+                .checked_add(stake_reward)
+                .ok_or(Errors::MathOverflow)?;
+
+            for (co_reward, stake_co_reward) in co_rewards.iter_mut().zip(stake_co_rewards) {
+                *co_reward = co_reward
+                    .checked_add(stake_co_reward)
+                    .ok_or(Errors::MathOverflow)?;
+            }
+        }
+
+        staked_weight += stake_details.multiplier as u64;
+
+        if unbonding_period > 0 {
+            // The NFT is returned with complete_unstake
+            stake_details.start_unbonding(unbonding_period)?;
+            unbonding_stakes.push(stake_details);
+            continue;
+        }
+
+        match stake_details.stake_mode {
+            StakeMode::Escrow => {
+                ctx.accounts.release_escrow(mint, nft_send_address, escrow_or_edition, &nft_seed[..])?
+            },
+            StakeMode::Freeze => {
+                ctx.accounts.thaw_nft(mint, nft_send_address, escrow_or_edition, metadata, &nft_seed[..])?
+            },
+            StakeMode::Lock => {
+                return Err(Errors::ProgrammableNftNotSupported.into());
+            }
+        }
+
+        returned += 1;
+        stake_details.close(ctx.accounts.owner.to_account_info())?;
+    }
+
+    let supply = ctx.accounts.token_mint.supply;
+    let vault_balance = ctx.accounts.reward_vault.as_ref().map(|reward_vault| reward_vault.amount);
+    let available = ctx.accounts.collection.reward_available(supply, vault_balance)?;
+    let payout = ctx.accounts.collection.claim_emission(reward, available);
+
+    let token_bump = ctx.accounts.collection.token_authority_bump;
+    let token_seed = &[&b"token-authority"[..], collection_key.as_ref(), &[token_bump]];
+
+    if payout > 0 {
+        utils::pay_reward(
+            payout,
+            &ctx.accounts.token_mint,
+            &ctx.accounts.token_receive_address,
+            ctx.accounts.reward_vault.as_deref(),
+            &ctx.accounts.token_authority,
+            &ctx.accounts.reward_token_program.to_account_info(),
+            &token_seed[..]
+        )?;
+    }
+
+    let token_authority = ctx.accounts.token_authority.to_account_info();
+    let co_payouts = utils::pay_co_rewards(
+        &mut ctx.accounts.collection.co_rewards,
+        &co_rewards,
+        co_reward_accounts,
+        &owner,
+        &token_authority,
+        &token_seed[..]
+    )?;
+
+    let unpaid_reward = reward - payout;
+    let unpaid_co_rewards = utils::unpaid_rewards(&co_rewards, &co_payouts);
+
+    // The unpaid rest is kept on an unbonding stake (paid by its complete_unstake),
+    // otherwise it's only forfeited if the holder opted to
+    match unbonding_stakes.first_mut() {
+        Some(stake_details) => stake_details.keep_unpaid(unpaid_reward, &unpaid_co_rewards)?,
+        None => utils::require_paid(unpaid_reward, &unpaid_co_rewards, forfeit_unpaid)?
+    }
+
+    for stake_details in &unbonding_stakes {
+        stake_details.exit(&ID)?;
+    }
+
+    // The unbonding NFTs stay counted until complete_unstake
+    ctx.accounts.collection.remove_stakes(returned, staked_weight)?;
+
+    Ok(())
+}
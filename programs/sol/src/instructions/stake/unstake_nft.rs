@@ -1,14 +1,27 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     token::{
-        TokenAccount,Mint,Token,Transfer,MintTo,
+        TokenAccount,Mint,Token,Transfer,Revoke,
         self, CloseAccount
     },
+    token_interface::{
+        TokenAccount as RewardTokenAccount,
+        Mint as RewardMint,
+        TokenInterface
+    },
+    metadata::{
+        self as token_metadata,
+        Metadata,
+        ThawDelegatedAccount
+    },
     associated_token::AssociatedToken
 };
 
-use crate::states::{Collection,Stake};
-use crate::{ ID, utils};
+use anchor_lang::solana_program::sysvar;
+
+use crate::states::{Collection,Stake,StakeMode,PausableOperation};
+use crate::utils::{self, ProgrammableNft};
+use crate::{Errors, MPL_TOKEN_METADATA_ID};
 
 #[derive(Accounts)]
 pub struct UnstakeNft<'info> {
@@ -21,8 +34,7 @@ pub struct UnstakeNft<'info> {
         bump,
         has_one = collection,
         has_one = nft_send_address,
-        has_one = owner,
-        close = owner
+        has_one = owner
     )]
     pub stake_details: Box<Account<'info,Stake>>,
 
@@ -34,16 +46,18 @@ pub struct UnstakeNft<'info> {
         ],
         bump,
         token::mint = nft_mint,
+        token::authority = nft_authority
     )]
-    pub nft_escrow: Account<'info,TokenAccount>,
+    pub nft_escrow: Option<Account<'info,TokenAccount>>,
 
+    #[account(mut)]
     pub collection: Box<Account<'info,Collection>>,
 
+    /// The token account the NFT was staked from (as recorded in the stake), it gets the NFT back
     #[account(
-        init_if_needed,
-        payer = owner, 
-        associated_token::mint = nft_mint, 
-        associated_token::authority = owner
+        mut,
+        token::mint = nft_mint,
+        token::authority = owner
     )]
     pub nft_send_address: Account<'info,TokenAccount>,
 
@@ -51,86 +65,321 @@ pub struct UnstakeNft<'info> {
         init_if_needed,
         payer = owner, 
         associated_token::mint = token_mint, 
-        associated_token::authority = owner
+        associated_token::authority = owner,
+        associated_token::token_program = reward_token_program
     )]
-    pub token_receive_address: Account<'info,TokenAccount>,
+    pub token_receive_address: InterfaceAccount<'info,RewardTokenAccount>,
 
     #[account(mint::decimals = 0)]
     pub nft_mint: Account<'info, Mint>,
 
-    #[account(mut)]
-    pub token_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = Some(token_mint.key()) == collection.token_mint @ Errors::TokenNotFound
+    )]
+    pub token_mint: InterfaceAccount<'info, RewardMint>,
 
     #[account(
-        seeds = [b"token-authority"],
-        bump
+        seeds = [b"token-authority", collection.key().as_ref()],
+        bump = collection.token_authority_bump
     )]
     pub token_authority: AccountInfo<'info>,
 
+    /// The reward vault of the collection (if the rewards are paid from it)
     #[account(
-        seeds = [b"nft-authority"],
-        bump
+        mut,
+        constraint = Some(reward_vault.key()) == collection.reward_vault @ Errors::RewardVaultNotFound
+    )]
+    pub reward_vault: Option<Box<InterfaceAccount<'info, RewardTokenAccount>>>,
+
+    #[account(
+        seeds = [b"nft-authority", collection.key().as_ref()],
+        bump = collection.nft_authority_bump
     )]
     pub nft_authority: AccountInfo<'info>,
 
+    /// CHECK: The master edition of the NFT (the freeze authority of the mint)
+    #[account(
+        seeds = [
+            b"metadata", 
+            MPL_TOKEN_METADATA_ID.as_ref(), 
+            nft_mint.key().as_ref(),
+            b"edition"
+        ],
+        seeds::program = MPL_TOKEN_METADATA_ID,
+        bump
+    )]
+    pub edition: Option<AccountInfo<'info>>,
+
+    /// CHECK: The metadata of the NFT (freeze and lock modes, validated by the seeds)
+    #[account(
+        mut,
+        seeds = [
+            b"metadata", 
+            MPL_TOKEN_METADATA_ID.as_ref(), 
+            nft_mint.key().as_ref()
+        ],
+        seeds::program = MPL_TOKEN_METADATA_ID,
+        bump
+    )]
+    pub metadata: Option<AccountInfo<'info>>,
+
+    /// CHECK: The token record of the pNFT (validated by the token metadata program)
+    #[account(
+        mut,
+        seeds = [
+            b"metadata", 
+            MPL_TOKEN_METADATA_ID.as_ref(), 
+            nft_mint.key().as_ref(),
+            b"token_record",
+            nft_send_address.key().as_ref()
+        ],
+        seeds::program = MPL_TOKEN_METADATA_ID,
+        bump
+    )]
+    pub token_record: Option<AccountInfo<'info>>,
+
+    /// CHECK: The rule set of the pNFT (validated by the token metadata program)
+    pub authorization_rules: Option<AccountInfo<'info>>,
+
+    /// CHECK: The program of the rule set (validated by the token metadata program)
+    pub authorization_rules_program: Option<AccountInfo<'info>>,
+
+    /// CHECK: The instructions sysvar (required by the token metadata program for the pNFTs)
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<AccountInfo<'info>>,
+
     #[account(mut)]
     pub owner: Signer<'info>,
 
     pub system_program: Program<'info,System>,
     pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>
+    /// The program of the collection token (Token or Token-2022)
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Option<Program<'info, Metadata>>
 }
 
 impl<'info> UnstakeNft<'info> {
-    pub fn transfer_nft_context(&self) -> CpiContext<'_,'_,'_,'info, Transfer<'info>> {
+    pub fn transfer_nft_context(
+        &self,
+        nft_escrow: &Account<'info,TokenAccount>
+    ) -> CpiContext<'_,'_,'_,'info, Transfer<'info>> {
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = Transfer {
-            from: self.nft_escrow.to_account_info(),
+            from: nft_escrow.to_account_info(),
             to: self.nft_send_address.to_account_info(),
             authority: self.nft_authority.to_account_info()
         };
         CpiContext::new(cpi_program, cpi_accounts)
     }
 
-    pub fn mint_token_context(&self) -> CpiContext<'_,'_,'_,'info, MintTo<'info>> {
-        let cpi_program = self.token_program.to_account_info();
-        let cpi_accounts = MintTo {
-            mint: self.token_mint.to_account_info(),
-            to: self.token_receive_address.to_account_info(),
-            authority: self.token_authority.to_account_info()
-        };
-        CpiContext::new(cpi_program, cpi_accounts)
-    }
-
-    pub fn close_account_context(&self) -> CpiContext<'_,'_,'_,'info, CloseAccount<'info>> {
+    pub fn close_account_context(
+        &self,
+        nft_escrow: &Account<'info,TokenAccount>
+    ) -> CpiContext<'_,'_,'_,'info, CloseAccount<'info>> {
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = CloseAccount {
-            account: self.nft_escrow.to_account_info(),
+            account: nft_escrow.to_account_info(),
             destination: self.owner.to_account_info(),
             authority: self.nft_authority.to_account_info()
         };
         CpiContext::new(cpi_program, cpi_accounts)
     }
-}
 
-pub fn unstake_nft_handler(ctx: Context<UnstakeNft>) -> Result<()> {
-    let emission = ctx.accounts.collection.emission;
-    let stake_time = ctx.accounts.stake_details.time;
+    /// Returns the NFT from the escrow and closes the escrow
+    pub fn release_escrow(&self, nft_seed: &[&[u8]]) -> Result<()> {
+        let nft_escrow = match self.nft_escrow.as_ref() {
+            Some(nft_escrow) => nft_escrow,
+            None => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
 
-    let reward = utils::calc_emission(stake_time, emission);
+        token::transfer(self.transfer_nft_context(nft_escrow).with_signer(&[nft_seed]), 1)?;
 
-    let (_nft_authority, nft_bump) = Pubkey::find_program_address(&[b"nft-authority"], &ID);
-    let nft_seed = &[&b"nft-authority"[..], &[nft_bump]];
+        token::close_account(self.close_account_context(nft_escrow).with_signer(&[nft_seed]))
+    }
 
-    let (_token_authority, token_bump) = Pubkey::find_program_address(&[b"token-authority"], &ID);
-    let token_seed = &[&b"token-authority"[..], &[token_bump]];
+    /// Thaws the NFT in the token account of the owner and revokes the delegate
+    pub fn thaw_nft(&self, nft_seed: &[&[u8]]) -> Result<()> {
+        let (metadata, edition, metadata_program) = match (
+            self.metadata.as_ref(),
+            self.edition.as_ref(),
+            self.metadata_program.as_ref()
+        ) {
+            (Some(metadata), Some(edition), Some(metadata_program)) => (metadata, edition, metadata_program),
+            _ => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
 
-    token::transfer(ctx.accounts.transfer_nft_context().with_signer(&[&nft_seed[..]]), 1)?;
+        let cpi_accounts = ThawDelegatedAccount {
+            metadata: metadata.to_account_info(),
+            delegate: self.nft_authority.to_account_info(),
+            token_account: self.nft_send_address.to_account_info(),
+            edition: edition.to_account_info(),
+            mint: self.nft_mint.to_account_info(),
+            token_program: self.token_program.to_account_info()
+        };
+        let cpi_context = CpiContext::new(metadata_program.to_account_info(), cpi_accounts);
 
-    token::mint_to(ctx.accounts.mint_token_context().with_signer(&[&token_seed[..]]), reward)?;
+        token_metadata::thaw_delegated_account(cpi_context.with_signer(&[nft_seed]))?;
 
-    token::close_account(ctx.accounts.close_account_context().with_signer(&[&nft_seed[..]]))?;
+        let cpi_accounts = Revoke {
+            source: self.nft_send_address.to_account_info(),
+            authority: self.owner.to_account_info()
+        };
+        token::revoke(CpiContext::new(self.token_program.to_account_info(), cpi_accounts))
+    }
+
+    /// Unlocks the pNFT in the token account of the owner and revokes the staking delegate
+    pub fn unlock_nft(&self, nft_seed: &[&[u8]]) -> Result<()> {
+        let (metadata, edition, token_record, sysvar_instructions, metadata_program) = match (
+            self.metadata.as_ref(),
+            self.edition.as_ref(),
+            self.token_record.as_ref(),
+            self.sysvar_instructions.as_ref(),
+            self.metadata_program.as_ref()
+        ) {
+            (
+                Some(metadata),
+                Some(edition),
+                Some(token_record),
+                Some(sysvar_instructions),
+                Some(metadata_program)
+            ) => {
+                (metadata, edition, token_record, sysvar_instructions, metadata_program)
+            },
+            _ => {
+                return Err(Errors::AccountNotProvided.into());
+            }
+        };
+
+        let mint = self.nft_mint.to_account_info();
+        let token = self.nft_send_address.to_account_info();
+        let owner = self.owner.to_account_info();
+        let system_program = self.system_program.to_account_info();
+        let token_program = self.token_program.to_account_info();
+        let metadata_program = metadata_program.to_account_info();
+
+        let programmable_nft = ProgrammableNft {
+            metadata,
+            edition,
+            token_record,
+            mint: &mint,
+            token: &token,
+            owner: &owner,
+            delegate: &self.nft_authority,
+            system_program: &system_program,
+            sysvar_instructions,
+            token_program: &token_program,
+            metadata_program: &metadata_program,
+            authorization_rules_program: self.authorization_rules_program.as_ref(),
+            authorization_rules: self.authorization_rules.as_ref()
+        };
+
+        programmable_nft.unlock(&[nft_seed])?;
+
+        programmable_nft.revoke()
+    }
 
-    Ok(())
+    /// Returns the NFT as it was staked (from the escrow, frozen or locked)
+    pub fn return_nft(&self) -> Result<()> {
+        let collection_key = self.collection.key();
+        let nft_bump = self.collection.nft_authority_bump;
+        let nft_seed = &[&b"nft-authority"[..], collection_key.as_ref(), &[nft_bump]];
+
+        match self.stake_details.stake_mode {
+            StakeMode::Escrow => self.release_escrow(&nft_seed[..]),
+            StakeMode::Freeze => self.thaw_nft(&nft_seed[..]),
+            StakeMode::Lock => self.unlock_nft(&nft_seed[..])
+        }
+    }
+
+    /// Pays the rewards, capped by the budget and the vault, and returns the unpaid rest
+    /// * the co-rewards are paid from their accounts in the remaining accounts
+    pub fn pay_rewards(
+        &mut self,
+        reward: u64,
+        co_rewards: &[u64; Collection::MAX_CO_REWARDS],
+        co_reward_accounts: &[AccountInfo<'info>]
+    ) -> Result<(u64, [u64; Collection::MAX_CO_REWARDS])> {
+        let supply = self.token_mint.supply;
+        let vault_balance = self.reward_vault.as_ref().map(|reward_vault| reward_vault.amount);
+        let available = self.collection.reward_available(supply, vault_balance)?;
+        let payout = self.collection.claim_emission(reward, available);
+
+        let collection_key = self.collection.key();
+        let token_bump = self.collection.token_authority_bump;
+        let token_seed = &[&b"token-authority"[..], collection_key.as_ref(), &[token_bump]];
+
+        if payout > 0 {
+            utils::pay_reward(
+                payout,
+                &self.token_mint,
+                &self.token_receive_address,
+                self.reward_vault.as_deref(),
+                &self.token_authority,
+                &self.reward_token_program.to_account_info(),
+                &token_seed[..]
+            )?;
+        }
+
+        let owner = self.owner.key();
+        let token_authority = self.token_authority.to_account_info();
+        let co_payouts = utils::pay_co_rewards(
+            &mut self.collection.co_rewards,
+            co_rewards,
+            co_reward_accounts,
+            &owner,
+            &token_authority,
+            &token_seed[..]
+        )?;
+
+        Ok((reward - payout, utils::unpaid_rewards(co_rewards, &co_payouts)))
+    }
 }
 
+pub fn unstake_nft_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, UnstakeNft<'info>>,
+    forfeit_unpaid: bool
+) -> Result<()> {
+    ctx.accounts.collection.check_operation(PausableOperation::UnstakeNft)?;
+
+    let is_early_exit = ctx.accounts.stake_details.check_unstake()?;
+
+    ctx.accounts.collection.update_reward_index()?;
+
+    let reward_index = ctx.accounts.collection.reward_index;
+    let reward = ctx.accounts.stake_details.claim_reward(reward_index)?;
+    let co_rewards = ctx.accounts.stake_details.claim_co_rewards(&ctx.accounts.collection.co_rewards)?;
+
+    // The early exit forfeits the accrued rewards
+    let (reward, co_rewards) = if is_early_exit {
+        (0, [0; Collection::MAX_CO_REWARDS])
+    } else {
+        (reward, co_rewards)
+    };
+
+    let (unpaid_reward, unpaid_co_rewards) = ctx.accounts.pay_rewards(reward, &co_rewards, ctx.remaining_accounts)?;
+
+    // The NFT stops earning (and leaves the emission pool) from now on
+    let multiplier = ctx.accounts.stake_details.multiplier;
+    ctx.accounts.collection.remove_stakes(0, multiplier as u64)?;
+
+    let unbonding_period = ctx.accounts.collection.unbonding_period;
+    if unbonding_period > 0 {
+        // The NFT is returned with complete_unstake, which pays the unpaid rest
+        ctx.accounts.stake_details.keep_unpaid(unpaid_reward, &unpaid_co_rewards)?;
+        return ctx.accounts.stake_details.start_unbonding(unbonding_period);
+    }
+
+    // The stake is closed, the unpaid rest is only forfeited if the holder opted to
+    utils::require_paid(unpaid_reward, &unpaid_co_rewards, forfeit_unpaid)?;
+
+    ctx.accounts.return_nft()?;
+
+    ctx.accounts.collection.remove_stakes(1, 0)?;
+    ctx.accounts.stake_details.close(ctx.accounts.owner.to_account_info())
+}
@@ -1,15 +1,16 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    token::{
-        TokenAccount, Mint,
-        Token, MintTo,
-        self
+    token::Mint,
+    token_interface::{
+        TokenAccount,
+        Mint as RewardMint,
+        TokenInterface
     },
     associated_token::AssociatedToken
 };
 
-use crate::states::{Collection,Stake};
-use crate::{ ID, utils};
+use crate::states::{Collection,Stake,PausableOperation};
+use crate::{Errors, utils};
 
 #[derive(Accounts)]
 pub struct WithdrawTokens<'info> {
@@ -25,58 +26,102 @@ pub struct WithdrawTokens<'info> {
     )]
     pub stake_details: Box<Account<'info,Stake>>,
 
+    #[account(mut)]
     pub collection: Box<Account<'info,Collection>>,
 
     #[account(
         init_if_needed,
         payer = owner, 
         associated_token::mint = token_mint, 
-        associated_token::authority = owner
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
     )]
-    pub token_receive_address: Account<'info,TokenAccount>,
+    pub token_receive_address: InterfaceAccount<'info,TokenAccount>,
 
     #[account(mint::decimals = 0)]
     pub nft_mint: Account<'info, Mint>,
 
-    #[account(mut)]
-    pub token_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = Some(token_mint.key()) == collection.token_mint @ Errors::TokenNotFound
+    )]
+    pub token_mint: InterfaceAccount<'info, RewardMint>,
 
     #[account(
-        seeds = [b"token-authority"],
-        bump
+        seeds = [b"token-authority", collection.key().as_ref()],
+        bump = collection.token_authority_bump
     )]
     pub token_authority: AccountInfo<'info>,
 
+    /// The reward vault of the collection (if the rewards are paid from it)
+    #[account(
+        mut,
+        constraint = Some(reward_vault.key()) == collection.reward_vault @ Errors::RewardVaultNotFound
+    )]
+    pub reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     #[account(mut)]
     pub owner: Signer<'info>,
 
     pub system_program: Program<'info,System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>
 }
 
-impl<'info> WithdrawTokens<'info> {
-    pub fn mint_token_context(&self) -> CpiContext<'_,'_,'_,'info, MintTo<'info>> {
-        let cpi_program = self.token_program.to_account_info();
-        let cpi_accounts = MintTo {
-            mint: self.token_mint.to_account_info(),
-            to: self.token_receive_address.to_account_info(),
-            authority: self.token_authority.to_account_info()
-        };
-        CpiContext::new(cpi_program, cpi_accounts)
+pub fn withdraw_tokens_handler<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawTokens<'info>>) -> Result<()> {
+    ctx.accounts.collection.check_operation(PausableOperation::WithdrawTokens)?;
+
+    let is_stake_active = ctx.accounts.collection.is_staking;
+    require_eq!(is_stake_active,true, Errors::ModuleNotActive);
+
+    ctx.accounts.stake_details.check_claim()?;
+
+    ctx.accounts.collection.update_reward_index()?;
+
+    let reward_index = ctx.accounts.collection.reward_index;
+    let reward = ctx.accounts.stake_details.claim_reward(reward_index)?;
+
+    let co_rewards = ctx.accounts.stake_details.claim_co_rewards(&ctx.accounts.collection.co_rewards)?;
+
+    let supply = ctx.accounts.token_mint.supply;
+    let vault_balance = ctx.accounts.reward_vault.as_ref().map(|reward_vault| reward_vault.amount);
+    let available = ctx.accounts.collection.reward_available(supply, vault_balance)?;
+    let payout = ctx.accounts.collection.claim_emission(reward, available);
+
+    let collection_key = ctx.accounts.collection.key();
+    let token_bump = ctx.accounts.collection.token_authority_bump;
+    let token_seed = &[&b"token-authority"[..], collection_key.as_ref(), &[token_bump]];
+
+    utils::pay_reward(
+        payout,
+        &ctx.accounts.token_mint,
+        &ctx.accounts.token_receive_address,
+        ctx.accounts.reward_vault.as_deref(),
+        &ctx.accounts.token_authority,
+        &ctx.accounts.token_program.to_account_info(),
+        &token_seed[..]
+    )?;
+
+    // The co-rewards are paid from the accounts at the end of the remaining accounts
+    let owner = ctx.accounts.owner.key();
+    let token_authority = ctx.accounts.token_authority.to_account_info();
+    let co_payouts = utils::pay_co_rewards(
+        &mut ctx.accounts.collection.co_rewards,
+        &co_rewards,
+        ctx.remaining_accounts,
+        &owner,
+        &token_authority,
+        &token_seed[..]
+    )?;
+
+    // Fails if nothing is left of any of the accrued rewards
+    if reward > 0 || co_rewards.iter().any(|co_reward| *co_reward > 0) {
+        require!(payout > 0 || co_payouts.iter().any(|co_payout| *co_payout > 0), Errors::EmissionBudgetExhausted);
     }
-}
-
-pub fn withdraw_tokens_handler(ctx: Context<WithdrawTokens>) -> Result<()> {
-    let emission = ctx.accounts.collection.emission;
-    let stake_time = ctx.accounts.stake_details.time;
-
-    let reward = utils::calc_emission(stake_time, emission);
-
-    let (_token_authority, token_bump) = Pubkey::find_program_address(&[b"token-authority"], &ID);
-    let token_seed = &[&b"token-authority"[..], &[token_bump]];
 
-    token::mint_to(ctx.accounts.mint_token_context().with_signer(&[&token_seed[..]]), reward)?;
+    // The rest of a partial payout is paid by a later claim
+    let unpaid_co_rewards = utils::unpaid_rewards(&co_rewards, &co_payouts);
+    ctx.accounts.stake_details.keep_unpaid(reward - payout, &unpaid_co_rewards)?;
 
     let clock = Clock::get().unwrap();
     let current_time = clock.unix_timestamp;
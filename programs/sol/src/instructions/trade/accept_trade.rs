@@ -1,13 +1,13 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token::{
+use anchor_spl::{token_interface::{
     self,
-    {TokenAccount,Mint,Token},
-    Transfer,
+    {TokenAccount,Mint,TokenInterface},
     SetAuthority,
-    spl_token::instruction::AuthorityType
+    spl_token_2022::instruction::AuthorityType
 }, associated_token::AssociatedToken};
 use anchor_lang::system_program;
-use crate::states::{Trade,TradeType, Collection};
+use crate::states::{Trade,TradeType, Collection,PausableOperation};
+use crate::utils::{transfer_checked_with_hook, ProgrammableTransfer};
 use crate::{Errors, ID,};
 
 #[derive(Accounts)]
@@ -34,17 +34,19 @@ pub struct AcceptTrade<'info> {
         ],
         bump,
         token::mint = two_mint,
-        token::authority = party_two
+        token::authority = party_two,
+        token::token_program = two_token_program
     )]
-    pub escrow_party_two: Option<Account<'info, TokenAccount>>,
+    pub escrow_party_two: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = party_two, 
         associated_token::mint = one_mint, 
-        associated_token::authority = party_two
+        associated_token::authority = party_two,
+        associated_token::token_program = token_program
     )]
-    pub two_receive_address: Option<Account<'info, TokenAccount>>,
+    pub two_receive_address: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -52,14 +54,14 @@ pub struct AcceptTrade<'info> {
         token::authority = party_two,
         constraint = two_send_address.amount >= spl_amount @ Errors::InsufficientBalance
     )]
-    pub two_send_address: Option<Account<'info, TokenAccount>>,
+    pub two_send_address: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         constraint = one_mint.key() == trade_details.one_mint.unwrap() @ Errors::MintNotExist
     )]
-    pub one_mint: Option<Account<'info,Mint>>,
+    pub one_mint: Option<InterfaceAccount<'info,Mint>>,
 
-    pub two_mint: Option<Account<'info, Mint>>,
+    pub two_mint: Option<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
@@ -69,53 +71,104 @@ pub struct AcceptTrade<'info> {
     
     pub collection: Box<Account<'info, Collection>>,
 
+    /// CHECK: Only required to escrow a programmable NFT (the receiver of its transfer)
+    #[account(
+        seeds = [b"escrow", collection.key().as_ref()],
+        bump = collection.escrow_authority_bump
+    )]
+    pub escrow_authority: Option<AccountInfo<'info>>,
+
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// The program of the mint sent by the first party
+    pub token_program: Interface<'info, TokenInterface>,
+    /// The program of the mint sent by the second party
+    pub two_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>
 }
 
 impl<'info> AcceptTrade<'info> {
-    pub fn transfer_spl(&self,spl_amount: u64) -> Result<()> {
+    /// Moves the tokens into the escrow and returns the amount received by it
+    /// (less than the amount sent if the mint charges a transfer fee)
+    pub fn transfer_spl(
+        &mut self,
+        spl_amount: u64,
+        remaining_accounts: &[AccountInfo<'info>]
+    ) -> Result<u64> {
         require_gt!(spl_amount,0, Errors::TokenAmountZero);
 
-        match self.two_send_address.as_ref() {
-            Some(token_account) => {
-                let escrow = self.escrow_party_two.as_ref()
-                .expect("Escrow account is not provided");
-
-                token::transfer(
-                    self.transfer_spl_context(token_account,escrow),
-                    spl_amount
-                )?;
-
-                let (escrow_authority, _) = Pubkey::find_program_address(&[b"escrow"], &ID);
-
-                token::set_authority(
-                    self.set_authority_context(escrow), 
-                    AuthorityType::AccountOwner, 
-                    Some(escrow_authority)
-                )?;
-            },
-            None => {
+        let (two_send_address, two_mint) = match (
+            self.two_send_address.as_ref(),
+            self.two_mint.as_ref()
+        ) {
+            (Some(token_account), Some(mint)) => (token_account, mint),
+            _ => {
                 return Err(Errors::AccountNotProvided.into());
             }
+        };
+
+        let escrow = self.escrow_party_two.as_ref()
+        .expect("Escrow account is not provided");
+        let balance = escrow.amount;
+
+        let (escrow_authority, _) = Pubkey::find_program_address(
+            &[b"escrow", self.collection.key().as_ref()],
+            &ID
+        );
+
+        // The escrow is handed over before it's filled, a programmable NFT is frozen once in it
+        token_interface::set_authority(
+            self.set_authority_context(escrow), 
+            AuthorityType::AccountOwner, 
+            Some(escrow_authority)
+        )?;
+
+        let (programmable_transfer, remaining_accounts) = ProgrammableTransfer::split(
+            remaining_accounts,
+            &two_mint.key(),
+            &two_send_address.key(),
+            &escrow.key()
+        )?;
+
+        if let Some(programmable_transfer) = programmable_transfer {
+            // A programmable NFT (by the token standard of its metadata) is moved by the metadata program
+            require_eq!(spl_amount, 1, Errors::TokenNotOne);
+
+            let escrow_authority = self.escrow_authority.as_ref()
+                .ok_or(Errors::AccountNotProvided)?;
+
+            programmable_transfer.transfer(
+                &two_send_address.to_account_info(),
+                &self.party_two.to_account_info(),
+                &escrow.to_account_info(),
+                escrow_authority,
+                &two_mint.to_account_info(),
+                &self.party_two.to_account_info(),
+                &self.system_program.to_account_info(),
+                &self.two_token_program.to_account_info(),
+                &[]
+            )?;
+        } else {
+            transfer_checked_with_hook(
+                &self.two_token_program.to_account_info(),
+                &two_send_address.to_account_info(),
+                &two_mint.to_account_info(),
+                &escrow.to_account_info(),
+                &self.party_two.to_account_info(),
+                remaining_accounts,
+                spl_amount,
+                two_mint.decimals,
+                &[]
+            )?;
         }
-        Ok(())
-    }
 
-    pub fn transfer_spl_context(
-        &self, 
-        two_send_address: &Account<'info, TokenAccount>,
-        escrow: &Account<'info,TokenAccount>
-    ) 
-    -> CpiContext<'_,'_,'_,'info, Transfer<'info>> {
-        let cpi_program = self.token_program.to_account_info();
-        let cpi_accounts = Transfer {
-            from: two_send_address.to_account_info(),
-            to: escrow.to_account_info(),
-            authority: self.party_two.to_account_info()
-        };
-        CpiContext::new(cpi_program, cpi_accounts)
+        let escrow = self.escrow_party_two.as_mut()
+        .expect("Escrow account is not provided");
+        escrow.reload()?;
+
+        let received = escrow.amount - balance;
+        require_gt!(received, 0, Errors::TokenAmountZero);
+
+        Ok(received)
     }
 
     pub fn transfer_sol_context(&self) -> CpiContext<'_,'_,'_,'info, system_program::Transfer<'info>> {
@@ -129,9 +182,9 @@ impl<'info> AcceptTrade<'info> {
 
     pub fn set_authority_context(
         &self, 
-        escrow: &Account<'info,TokenAccount>
+        escrow: &InterfaceAccount<'info,TokenAccount>
     ) -> CpiContext<'_,'_,'_,'info, SetAuthority<'info>> {
-        let cpi_program = self.token_program.to_account_info();
+        let cpi_program = self.two_token_program.to_account_info();
         let cpi_accounts = SetAuthority {
             account_or_mint: escrow.to_account_info(),
             current_authority: self.party_two.to_account_info()
@@ -140,27 +193,31 @@ impl<'info> AcceptTrade<'info> {
     }
 }
 
-pub fn accept_trade_handler(
-    ctx: Context<AcceptTrade>,
+pub fn accept_trade_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, AcceptTrade<'info>>,
     sol_amount: u64,
     spl_amount: u64,
     trade_type: TradeType
 ) -> Result<()> {
+    ctx.accounts.collection.check_operation(PausableOperation::AcceptTrade)?;
+
+    let is_trade_active = ctx.accounts.collection.is_trade;
+    require_eq!(is_trade_active,true, Errors::ModuleNotActive);
+
     let confirm_status = ctx.accounts.trade_details.is_confirmed;
     
     require_eq!(confirm_status,false,Errors::TradeAlreadyAccepted);
 
+    // The amount settled later is what the escrow received (net of the transfer fee)
+    let mut escrowed_amount = 0;
+
     match trade_type {
         TradeType::Sol => {
             require_gt!(sol_amount,0, Errors::TokenAmountZero);
             require_eq!(spl_amount,0, Errors::AmountNotZero);
 
-            let escrow_party_two = &ctx.accounts.escrow_party_two;
-            match escrow_party_two {
-                Some(_escrow) => {
-                    return Err(Errors::AccountNotRequired.into());
-                },
-                _ => ()
+            if ctx.accounts.escrow_party_two.is_some() {
+                return Err(Errors::AccountNotRequired.into());
             }
 
             system_program::transfer(
@@ -170,12 +227,12 @@ pub fn accept_trade_handler(
         },
         TradeType::Spl => {
             require_eq!(sol_amount,0, Errors::AmountNotZero);            
-            ctx.accounts.transfer_spl(spl_amount)?;
+            escrowed_amount = ctx.accounts.transfer_spl(spl_amount, ctx.remaining_accounts)?;
         },
         TradeType::Both => {
             require_gt!(sol_amount,0, Errors::TokenAmountZero);
 
-            ctx.accounts.transfer_spl(spl_amount)?;
+            escrowed_amount = ctx.accounts.transfer_spl(spl_amount, ctx.remaining_accounts)?;
 
             system_program::transfer(
                 ctx.accounts.transfer_sol_context(),
@@ -184,12 +241,8 @@ pub fn accept_trade_handler(
         }
     };
 
-    let two_send_address = if let Some(token_account) =
-    ctx.accounts.two_send_address.as_ref() {
-        Some(token_account.key())
-    } else {
-        None
-    };
+    let two_send_address = ctx.accounts.two_send_address.as_ref()
+        .map(|token_account| token_account.key());
 
     let two_mint = if let Some(mint_account) =
     ctx.accounts.two_mint.as_ref() {
@@ -202,22 +255,11 @@ pub fn accept_trade_handler(
         None
     };
 
-    let two_receive_address = if let Some(token_account) =
-    ctx.accounts.two_receive_address.as_ref() {
-        Some(token_account.key())
-    } else {
-        None
-    };
-
-    let one_mint_wrap = ctx.accounts.trade_details.one_mint;
+    let two_receive_address = ctx.accounts.two_receive_address.as_ref()
+        .map(|token_account| token_account.key());
 
-    if let Some(_mint) = one_mint_wrap {
-        match two_receive_address {
-            None => {
-                return Err(Errors::AccountNotProvided.into());
-            },
-            _ => ()
-        }
+    if ctx.accounts.trade_details.one_mint.is_some() && two_receive_address.is_none() {
+        return Err(Errors::AccountNotProvided.into());
     }
 
     let trade_details = &mut ctx.accounts.trade_details;
@@ -227,7 +269,9 @@ pub fn accept_trade_handler(
     trade_details.two_send_address = two_send_address;
     trade_details.two_mint = two_mint;
     trade_details.sol_amount[1] = sol_amount;
-    trade_details.spl_amount[1] = spl_amount;
+    trade_details.spl_amount[1] = escrowed_amount;
+    // The fee charged on execution is the one in force when the trade is accepted
+    trade_details.trade_fee = ctx.accounts.collection.trade_fees;
     
     Ok(())
 }
@@ -1,13 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{
+use anchor_spl::token_interface::{
     self,
     TokenAccount,
-    Transfer,
     CloseAccount,
-    Token
+    TokenInterface,
+    Mint
 };
-use crate::states::{Trade, Collection};
-use crate::{Errors, ID,};
+use crate::states::{Trade, Collection, PausableOperation};
+use crate::utils::{transfer_checked_with_hook, harvest_withheld_fees, ProgrammableTransfer};
+use crate::Errors;
 
 #[derive(Accounts)]
 pub struct CancelTrade<'info> {
@@ -28,9 +29,11 @@ pub struct CancelTrade<'info> {
             trade_details.party_two.key().as_ref(),
             collection.key().as_ref()
         ],
-        bump
+        bump,
+        token::authority = escrow_authority,
+        token::token_program = token_program
     )]
-    pub escrow_party_one: Option<Account<'info, TokenAccount>>,
+    pub escrow_party_one: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -40,21 +43,33 @@ pub struct CancelTrade<'info> {
             trade_details.party_two.key().as_ref(),
             collection.key().as_ref()
         ],
-        bump
+        bump,
+        token::authority = escrow_authority,
+        token::token_program = two_token_program
     )]
-    pub escrow_party_two: Option<Account<'info, TokenAccount>>,
+    pub escrow_party_two: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         address = trade_details.one_send_address.unwrap() @ Errors::IncorrectTokenAccount
     )]
-    pub one_send_address: Option<Account<'info, TokenAccount>>,
+    pub one_send_address: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         address = trade_details.two_send_address.unwrap() @ Errors::IncorrectTokenAccount
     )]
-    pub two_send_address: Option<Account<'info, TokenAccount>>,
+    pub two_send_address: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        address = trade_details.one_mint.unwrap() @ Errors::MintNotExist
+    )]
+    pub one_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        address = trade_details.two_mint.unwrap() @ Errors::MintNotExist
+    )]
+    pub two_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
 
     #[account(mut)]
     pub party_one: Signer<'info>,
@@ -63,67 +78,115 @@ pub struct CancelTrade<'info> {
     #[account(mut)] 
     pub party_two: AccountInfo<'info>,
     
+    #[account(mut)]
     pub collection: Box<Account<'info, Collection>>,
 
     #[account(
-        seeds = [b"escrow"],
-        bump
+        seeds = [b"escrow", collection.key().as_ref()],
+        bump = collection.escrow_authority_bump
     )]
     pub escrow_authority: AccountInfo<'info>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// The program of the mint sent by the first party
+    pub token_program: Interface<'info, TokenInterface>,
+    /// The program of the mint sent by the second party
+    pub two_token_program: Interface<'info, TokenInterface>,
 }
 
 impl<'info> CancelTrade<'info> {
-    pub fn transfer_spl(&self, party: u8, amount: u64) -> Result<()> {
-        let (_escrow_authority, bump) = Pubkey::find_program_address(&[b"escrow"], &ID);
-        let escrow_seed = &[&b"escrow"[..], &[bump]];
-
-        let transfer_cpi_program = self.token_program.to_account_info();
-        let close_cpi_program = self.token_program.to_account_info();
-
-        let transfer_cpi_accounts = if party == 0 {
-            Transfer {
-                from: self.escrow_party_one.as_ref().unwrap().to_account_info(),
-                to: self.one_send_address.as_ref().unwrap().to_account_info(),
-                authority: self.escrow_authority.to_account_info()
-            }
-        } else {
-            Transfer {
-                from: self.escrow_party_two.as_ref().unwrap().to_account_info(),
-                to: self.two_send_address.as_ref().unwrap().to_account_info(),
-                authority: self.escrow_authority.to_account_info()
-            }
-        };
-        
-        let close_cpi_accounts = if party == 0 {
-            CloseAccount {
-                account: self.escrow_party_one.as_ref().unwrap().to_account_info(),
-                destination: self.party_one.to_account_info(),
-                authority: self.escrow_authority.to_account_info()
+    /// Settles the escrow of the party: sends its whole balance, moves the withheld
+    /// transfer fees to the mint (if any) and closes the escrow
+    /// * returns the remaining accounts left for the other leg (after those of a programmable NFT)
+    pub fn transfer_spl<'a>(
+        &self,
+        party: u8,
+        remaining_accounts: &'a [AccountInfo<'info>]
+    ) -> Result<&'a [AccountInfo<'info>]> {
+        let collection_key = self.collection.key();
+        let bump = self.collection.escrow_authority_bump;
+        let escrow_seed = &[&b"escrow"[..], collection_key.as_ref(), &[bump]];
+
+        let (escrow, to, mint, token_program, destination, receiver) = if party == 0 {
+            match (
+                self.escrow_party_one.as_ref(),
+                self.one_send_address.as_ref(),
+                self.one_mint.as_ref()
+            ) {
+                (Some(escrow), Some(to), Some(mint)) => {
+                    (escrow, to, mint, &self.token_program, self.party_one.to_account_info(), self.party_one.to_account_info())
+                },
+                _ => {
+                    return Err(Errors::AccountNotProvided.into());
+                }
             }
         } else {
-            CloseAccount {
-                account: self.escrow_party_two.as_ref().unwrap().to_account_info(),
-                destination: self.party_two.to_account_info(),
-                authority: self.escrow_authority.to_account_info()
+            match (
+                self.escrow_party_two.as_ref(),
+                self.two_send_address.as_ref(),
+                self.two_mint.as_ref()
+            ) {
+                (Some(escrow), Some(to), Some(mint)) => {
+                    (escrow, to, mint, &self.two_token_program, self.party_two.to_account_info(), self.party_two.to_account_info())
+                },
+                _ => {
+                    return Err(Errors::AccountNotProvided.into());
+                }
             }
         };
 
-        let transfer_context = CpiContext::new(transfer_cpi_program, transfer_cpi_accounts);
-        let close_context = CpiContext::new(close_cpi_program, close_cpi_accounts);
+        let (programmable_transfer, remaining_accounts) = ProgrammableTransfer::split(
+            remaining_accounts,
+            &mint.key(),
+            &escrow.key(),
+            &to.key()
+        )?;
+
+        if let Some(programmable_transfer) = programmable_transfer {
+            // A programmable NFT (by the token standard of its metadata) is moved by the metadata program
+            programmable_transfer.transfer(
+                &escrow.to_account_info(),
+                &self.escrow_authority.to_account_info(),
+                &to.to_account_info(),
+                &receiver,
+                &mint.to_account_info(),
+                &self.party_one.to_account_info(),
+                &self.system_program.to_account_info(),
+                &token_program.to_account_info(),
+                &[&escrow_seed[..]]
+            )?;
+        } else {
+            transfer_checked_with_hook(
+                &token_program.to_account_info(),
+                &escrow.to_account_info(),
+                &mint.to_account_info(),
+                &to.to_account_info(),
+                &self.escrow_authority.to_account_info(),
+                remaining_accounts,
+                escrow.amount,
+                mint.decimals,
+                &[&escrow_seed[..]]
+            )?;
+        }
 
-        token::transfer(
-            transfer_context.with_signer(&[&escrow_seed[..]]),
-            amount
+        harvest_withheld_fees(
+            &token_program.to_account_info(),
+            &mint.to_account_info(),
+            &escrow.to_account_info()
         )?;
 
-        token::close_account(
+        let close_cpi_accounts = CloseAccount {
+            account: escrow.to_account_info(),
+            destination,
+            authority: self.escrow_authority.to_account_info()
+        };
+        let close_context = CpiContext::new(token_program.to_account_info(), close_cpi_accounts);
+
+        token_interface::close_account(
             close_context.with_signer(&[&escrow_seed[..]])
         )?;
             
-        Ok(())
+        Ok(remaining_accounts)
     }
 
     pub fn transfer_sol(&self,party: u8,amount: u64) -> Result<()> {
@@ -140,7 +203,9 @@ impl<'info> CancelTrade<'info> {
 
 }
 
-pub fn cancel_trade_handler(ctx: Context<CancelTrade>) -> Result<()> {
+pub fn cancel_trade_handler<'info>(ctx: Context<'_, '_, '_, 'info, CancelTrade<'info>>) -> Result<()> {
+    ctx.accounts.collection.check_operation(PausableOperation::CancelTrade)?;
+
     let clock = Clock::get()?;
     let time = clock.unix_timestamp;
 
@@ -149,12 +214,14 @@ pub fn cancel_trade_handler(ctx: Context<CancelTrade>) -> Result<()> {
 
 
     if trade_details.is_confirmed {
+        let mut remaining_accounts = ctx.remaining_accounts;
+
         if trade_details.spl_amount[0] > 0 {
-            ctx.accounts.transfer_spl(0, trade_details.spl_amount[0])?;
+            remaining_accounts = ctx.accounts.transfer_spl(0, remaining_accounts)?;
         }
 
         if trade_details.spl_amount[1] > 0 {
-            ctx.accounts.transfer_spl(1, trade_details.spl_amount[1])?;
+            ctx.accounts.transfer_spl(1, remaining_accounts)?;
 
         }
         
@@ -166,11 +233,14 @@ pub fn cancel_trade_handler(ctx: Context<CancelTrade>) -> Result<()> {
             ctx.accounts.transfer_sol(1, trade_details.sol_amount[1])?;
         }
     } else {
-        let trade_duration = collection_details.trade_duration;
-        require_gt!(time, trade_details.time + trade_duration, Errors::TradeTimeNotExpired);
+        // The trade can be cancelled right away once the trade module is removed
+        if collection_details.is_trade {
+            let trade_duration = collection_details.trade_duration;
+            require_gt!(time, trade_details.time + trade_duration, Errors::TradeTimeNotExpired);
+        }
 
         if trade_details.spl_amount[0] > 0 {
-            ctx.accounts.transfer_spl(0, trade_details.spl_amount[0])?;
+            ctx.accounts.transfer_spl(0, ctx.remaining_accounts)?;
         }
 
         if trade_details.sol_amount[0] > 0 {
@@ -178,5 +248,9 @@ pub fn cancel_trade_handler(ctx: Context<CancelTrade>) -> Result<()> {
         }
     }
 
+    let collection = &mut ctx.accounts.collection;
+    collection.open_trades = collection.open_trades.checked_sub(1)
+        .ok_or(Errors::MathOverflow)?;
+
     Ok(())
 }
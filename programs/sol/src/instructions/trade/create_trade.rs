@@ -1,14 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{
+use anchor_spl::token_interface::{
     self,
-    {TokenAccount,Mint,Token},
-    Transfer,
+    {TokenAccount,Mint,TokenInterface},
     SetAuthority,
-    spl_token::instruction::AuthorityType
+    spl_token_2022::instruction::AuthorityType
 };
 use anchor_lang::system_program;
 use anchor_spl::metadata::MetadataAccount;
-use crate::states::{Trade,TradeType,Collection};
+use crate::states::{Trade,TradeOffer,TradeType,Collection,PausableOperation};
+use crate::utils::{transfer_checked_with_hook, ProgrammableTransfer};
 use crate::{Errors, ID, MPL_TOKEN_METADATA_ID};
 
 #[derive(Accounts)]
@@ -42,9 +42,10 @@ pub struct CreateTrade<'info> {
         ],
         bump,
         token::mint = mint,
-        token::authority = party_one
+        token::authority = party_one,
+        token::token_program = token_program
     )]
-    pub escrow_party_one: Option<Account<'info, TokenAccount>>,
+    pub escrow_party_one: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -52,7 +53,7 @@ pub struct CreateTrade<'info> {
         token::authority = party_one,
         constraint = one_send_address.amount >= spl_amount @ Errors::InsufficientBalance
     )]
-    pub one_send_address: Option<Account<'info, TokenAccount>>,
+    pub one_send_address: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -60,13 +61,13 @@ pub struct CreateTrade<'info> {
     )]
     pub party_one: Signer<'info>,
 
-    pub mint: Option<Account<'info, Mint>>,
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
 
     #[account(
         token::authority = party_one,
         constraint = one_token_validation.amount == 1 @ Errors::TokenNotOne
     )]
-    pub one_token_validation: Box<Account<'info,TokenAccount>>,
+    pub one_token_validation: Box<InterfaceAccount<'info,TokenAccount>>,
 
     #[account(
         seeds = [
@@ -87,7 +88,7 @@ pub struct CreateTrade<'info> {
         token::authority = party_two,
         constraint = two_token_validation.amount == 1 @ Errors::TokenNotOne
     )]
-    pub two_token_validation: Box<Account<'info,TokenAccount>>,
+    pub two_token_validation: Box<InterfaceAccount<'info,TokenAccount>>,
 
     #[account(
         seeds = [
@@ -106,54 +107,103 @@ pub struct CreateTrade<'info> {
     /// CHECK: Nothing is read or written into this account
     pub party_two: AccountInfo<'info>,
 
+    #[account(mut)]
     pub collection_details: Box<Account<'info, Collection>>,
+
+    /// CHECK: Only required to escrow a programmable NFT (the receiver of its transfer)
+    #[account(
+        seeds = [b"escrow", collection_details.key().as_ref()],
+        bump = collection_details.escrow_authority_bump
+    )]
+    pub escrow_authority: Option<AccountInfo<'info>>,
     
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 impl<'info> CreateTrade<'info> {
-    pub fn transfer_spl(&self,spl_amount: u64) -> Result<()> {
+    /// Moves the tokens into the escrow and returns the amount received by it
+    /// (less than the amount sent if the mint charges a transfer fee)
+    pub fn transfer_spl(
+        &mut self,
+        spl_amount: u64,
+        remaining_accounts: &[AccountInfo<'info>]
+    ) -> Result<u64> {
         require_gt!(spl_amount,0, Errors::TokenAmountZero);
-        
-        match self.one_send_address.as_ref() {
-            Some(token_account) => {
-                let escrow = self.escrow_party_one.as_ref()
-                .expect("Escrow account is not provided");
-
-                token::transfer(
-                    self.transfer_spl_context(token_account,escrow),
-                    spl_amount
-                )?;
-
-                let (escrow_authority, _) = Pubkey::find_program_address(&[b"escrow"], &ID);
-
-                token::set_authority(
-                    self.set_authority_context(escrow), 
-                    AuthorityType::AccountOwner, 
-                    Some(escrow_authority)
-                )?;
-            },
-            None => {
+
+        let (one_send_address, mint) = match (
+            self.one_send_address.as_ref(),
+            self.mint.as_ref()
+        ) {
+            (Some(token_account), Some(mint)) => (token_account, mint),
+            _ => {
                 return Err(Errors::AccountNotProvided.into());
             }
+        };
+
+        let escrow = self.escrow_party_one.as_ref()
+        .expect("Escrow account is not provided");
+        let balance = escrow.amount;
+
+        let (escrow_authority, _) = Pubkey::find_program_address(
+            &[b"escrow", self.collection_details.key().as_ref()],
+            &ID
+        );
+
+        // The escrow is handed over before it's filled, a programmable NFT is frozen once in it
+        token_interface::set_authority(
+            self.set_authority_context(escrow), 
+            AuthorityType::AccountOwner, 
+            Some(escrow_authority)
+        )?;
+
+        let (programmable_transfer, remaining_accounts) = ProgrammableTransfer::split(
+            remaining_accounts,
+            &mint.key(),
+            &one_send_address.key(),
+            &escrow.key()
+        )?;
+
+        if let Some(programmable_transfer) = programmable_transfer {
+            // A programmable NFT (by the token standard of its metadata) is moved by the metadata program
+            require_eq!(spl_amount, 1, Errors::TokenNotOne);
+
+            let escrow_authority = self.escrow_authority.as_ref()
+                .ok_or(Errors::AccountNotProvided)?;
+
+            programmable_transfer.transfer(
+                &one_send_address.to_account_info(),
+                &self.party_one.to_account_info(),
+                &escrow.to_account_info(),
+                escrow_authority,
+                &mint.to_account_info(),
+                &self.party_one.to_account_info(),
+                &self.system_program.to_account_info(),
+                &self.token_program.to_account_info(),
+                &[]
+            )?;
+        } else {
+            transfer_checked_with_hook(
+                &self.token_program.to_account_info(),
+                &one_send_address.to_account_info(),
+                &mint.to_account_info(),
+                &escrow.to_account_info(),
+                &self.party_one.to_account_info(),
+                remaining_accounts,
+                spl_amount,
+                mint.decimals,
+                &[]
+            )?;
         }
-        Ok(())
-    }
 
-    pub fn transfer_spl_context(
-        &self, 
-        one_send_address: &Account<'info, TokenAccount>,
-        escrow: &Account<'info,TokenAccount>
-    ) 
-    -> CpiContext<'_,'_,'_,'info, Transfer<'info>> {
-        let cpi_program = self.token_program.to_account_info();
-        let cpi_accounts = Transfer {
-            from: one_send_address.to_account_info(),
-            to: escrow.to_account_info(),
-            authority: self.party_one.to_account_info()
-        };
-        CpiContext::new(cpi_program, cpi_accounts)
+        let escrow = self.escrow_party_one.as_mut()
+        .expect("Escrow account is not provided");
+        escrow.reload()?;
+
+        let received = escrow.amount - balance;
+        require_gt!(received, 0, Errors::TokenAmountZero);
+
+        Ok(received)
     }
 
     pub fn transfer_sol_context(&self) -> CpiContext<'_,'_,'_,'info, system_program::Transfer<'info>> {
@@ -167,7 +217,7 @@ impl<'info> CreateTrade<'info> {
 
     pub fn set_authority_context(
         &self, 
-        escrow: &Account<'info,TokenAccount>
+        escrow: &InterfaceAccount<'info,TokenAccount>
     ) -> CpiContext<'_,'_,'_,'info, SetAuthority<'info>> {
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = SetAuthority {
@@ -178,29 +228,30 @@ impl<'info> CreateTrade<'info> {
     }
 }
 
-pub fn create_trade_handler(
-    ctx: Context<CreateTrade>,
+pub fn create_trade_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateTrade<'info>>,
     sol_amount: u64,
     spl_amount: u64,
     trade_type: TradeType
 ) -> Result<()> {
+    ctx.accounts.collection_details.check_operation(PausableOperation::CreateTrade)?;
+
     let is_trade_active = ctx.accounts.collection_details.is_trade;
     require_eq!(is_trade_active,true, Errors::ModuleNotActive);
 
     let clock = Clock::get()?;
     let time = clock.unix_timestamp;
 
+    // The amount settled later is what the escrow received (net of the transfer fee)
+    let mut escrowed_amount = 0;
+
     match trade_type {
         TradeType::Sol => {
             require_gt!(sol_amount,0, Errors::TokenAmountZero);
             require_eq!(spl_amount,0, Errors::AmountNotZero);
 
-            let escrow_party_one = &ctx.accounts.escrow_party_one;
-            match escrow_party_one {
-                Some(_escrow) => {
-                    return Err(Errors::AccountNotRequired.into());
-                },
-                _ => ()
+            if ctx.accounts.escrow_party_one.is_some() {
+                return Err(Errors::AccountNotRequired.into());
             }
 
             system_program::transfer(
@@ -210,12 +261,12 @@ pub fn create_trade_handler(
         },
         TradeType::Spl => {            
             require_eq!(sol_amount,0, Errors::AmountNotZero);
-            ctx.accounts.transfer_spl(spl_amount)?;
+            escrowed_amount = ctx.accounts.transfer_spl(spl_amount, ctx.remaining_accounts)?;
         },
         TradeType::Both => {
             require_gt!(sol_amount,0, Errors::TokenAmountZero);
 
-            ctx.accounts.transfer_spl(spl_amount)?;
+            escrowed_amount = ctx.accounts.transfer_spl(spl_amount, ctx.remaining_accounts)?;
 
             system_program::transfer(
                 ctx.accounts.transfer_sol_context(),
@@ -228,12 +279,8 @@ pub fn create_trade_handler(
     let party_two = ctx.accounts.party_two.key();
     let collection = ctx.accounts.collection_details.key();
 
-    let one_send_address = if let Some(token_account) =
-    ctx.accounts.one_send_address.as_ref() {
-        Some(token_account.key())
-    } else {
-        None
-    };
+    let one_send_address = ctx.accounts.one_send_address.as_ref()
+        .map(|token_account| token_account.key());
 
     let one_mint = if let Some(mint_account) =
     ctx.accounts.mint.as_ref() {
@@ -246,16 +293,22 @@ pub fn create_trade_handler(
         None
     };
 
+    let offer = TradeOffer {
+        sol_amount,
+        spl_amount: escrowed_amount,
+        send_address: one_send_address,
+        mint: one_mint
+    };
+
     *ctx.accounts.trade_details = Trade::new(
         party_one, 
         party_two, 
-        sol_amount, 
-        spl_amount, 
-        one_send_address, 
+        offer, 
         time,
-        collection,
-        one_mint
+        collection
     );
 
+    ctx.accounts.collection_details.open_trades += 1;
+
     Ok(())
 }
@@ -1,16 +1,16 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token::{
+use anchor_spl::{token_interface::{
     self,
     TokenAccount,
-    Transfer,
     CloseAccount,
-    Token,
+    TokenInterface,
     Mint
 },associated_token::AssociatedToken
 };
 use anchor_lang::system_program;
-use crate::states::{Trade, Collection};
-use crate::{Errors, ID,};
+use crate::states::{Trade, Collection, PausableOperation};
+use crate::utils::{transfer_checked_with_hook, harvest_withheld_fees, ProgrammableTransfer};
+use crate::Errors;
 
 #[derive(Accounts)]
 pub struct ExecuteTrade<'info> {
@@ -31,9 +31,11 @@ pub struct ExecuteTrade<'info> {
             trade_details.party_two.key().as_ref(),
             collection.key().as_ref()
         ],
-        bump
+        bump,
+        token::authority = escrow_authority,
+        token::token_program = token_program
     )]
-    pub escrow_party_one: Option<Account<'info, TokenAccount>>,
+    pub escrow_party_one: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -43,27 +45,30 @@ pub struct ExecuteTrade<'info> {
             trade_details.party_two.key().as_ref(),
             collection.key().as_ref()
         ],
-        bump
+        bump,
+        token::authority = escrow_authority,
+        token::token_program = two_token_program
     )]
-    pub escrow_party_two: Option<Account<'info, TokenAccount>>,
+    pub escrow_party_two: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = party_one, 
         associated_token::mint = two_mint, 
-        associated_token::authority = party_one
+        associated_token::authority = party_one,
+        associated_token::token_program = two_token_program
     )]
-    pub one_receive_address: Option<Account<'info, TokenAccount>>,
+    pub one_receive_address: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         address = trade_details.two_receive_address.unwrap() @ Errors::IncorrectTokenAccount
     )]
-    pub two_receive_address: Option<Account<'info, TokenAccount>>,
+    pub two_receive_address: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
-        constraint = party_one.lamports() >= collection.trade_fees @ Errors::InsufficientBalance
+        constraint = party_one.lamports() >= trade_details.trade_fee @ Errors::InsufficientBalance
     )]
     pub party_one: Signer<'info>,
 
@@ -72,79 +77,132 @@ pub struct ExecuteTrade<'info> {
     pub party_two: AccountInfo<'info>,
     
     #[account(
+        mut,
         has_one = treasury_address,
     )]
     pub collection: Box<Account<'info, Collection>>,
 
     #[account(
-        seeds = [b"escrow"],
-        bump
+        seeds = [b"escrow", collection.key().as_ref()],
+        bump = collection.escrow_authority_bump
     )]
     pub escrow_authority: AccountInfo<'info>,
 
+    #[account(
+        address = trade_details.one_mint.unwrap() @ Errors::MintNotExist
+    )]
+    pub one_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
     #[account(
         address = trade_details.two_mint.unwrap() @ Errors::MintNotExist
     )]
-    pub two_mint: Option<Box<Account<'info, Mint>>>,
+    pub two_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
 
     /// CHECK: This account is customly validated
     #[account(mut)]
     pub treasury_address: AccountInfo<'info>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// The program of the mint sent by the first party
+    pub token_program: Interface<'info, TokenInterface>,
+    /// The program of the mint sent by the second party
+    pub two_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>
 }
 
 impl<'info> ExecuteTrade<'info> {
-    pub fn transfer_spl(&self, party: u8, amount: u64) -> Result<()> {
-        let (_escrow_authority, bump) = Pubkey::find_program_address(&[b"escrow"], &ID);
-        let escrow_seed = &[&b"escrow"[..], &[bump]];
-
-        let transfer_cpi_program = self.token_program.to_account_info();
-        let close_cpi_program = self.token_program.to_account_info();
-
-        let transfer_cpi_accounts = if party == 0 {
-            Transfer {
-                from: self.escrow_party_one.as_ref().unwrap().to_account_info(),
-                to: self.two_receive_address.as_ref().unwrap().to_account_info(),
-                authority: self.escrow_authority.to_account_info()
-            }
-        } else {
-            Transfer {
-                from: self.escrow_party_two.as_ref().unwrap().to_account_info(),
-                to: self.one_receive_address.as_ref().unwrap().to_account_info(),
-                authority: self.escrow_authority.to_account_info()
-            }
-        };
-        
-        let close_cpi_accounts = if party == 0 {
-            CloseAccount {
-                account: self.escrow_party_one.as_ref().unwrap().to_account_info(),
-                destination: self.party_one.to_account_info(),
-                authority: self.escrow_authority.to_account_info()
+    /// Settles the escrow of the party: sends its whole balance, moves the withheld
+    /// transfer fees to the mint (if any) and closes the escrow
+    /// * returns the remaining accounts left for the other leg (after those of a programmable NFT)
+    pub fn transfer_spl<'a>(
+        &self,
+        party: u8,
+        remaining_accounts: &'a [AccountInfo<'info>]
+    ) -> Result<&'a [AccountInfo<'info>]> {
+        let collection_key = self.collection.key();
+        let bump = self.collection.escrow_authority_bump;
+        let escrow_seed = &[&b"escrow"[..], collection_key.as_ref(), &[bump]];
+
+        let (escrow, to, mint, token_program, destination, receiver) = if party == 0 {
+            match (
+                self.escrow_party_one.as_ref(),
+                self.two_receive_address.as_ref(),
+                self.one_mint.as_ref()
+            ) {
+                (Some(escrow), Some(to), Some(mint)) => {
+                    (escrow, to, mint, &self.token_program, self.party_one.to_account_info(), self.party_two.to_account_info())
+                },
+                _ => {
+                    return Err(Errors::AccountNotProvided.into());
+                }
             }
         } else {
-            CloseAccount {
-                account: self.escrow_party_two.as_ref().unwrap().to_account_info(),
-                destination: self.party_two.to_account_info(),
-                authority: self.escrow_authority.to_account_info()
+            match (
+                self.escrow_party_two.as_ref(),
+                self.one_receive_address.as_ref(),
+                self.two_mint.as_ref()
+            ) {
+                (Some(escrow), Some(to), Some(mint)) => {
+                    (escrow, to, mint, &self.two_token_program, self.party_two.to_account_info(), self.party_one.to_account_info())
+                },
+                _ => {
+                    return Err(Errors::AccountNotProvided.into());
+                }
             }
         };
 
-        let transfer_context = CpiContext::new(transfer_cpi_program, transfer_cpi_accounts);
-        let close_context = CpiContext::new(close_cpi_program, close_cpi_accounts);
+        let (programmable_transfer, remaining_accounts) = ProgrammableTransfer::split(
+            remaining_accounts,
+            &mint.key(),
+            &escrow.key(),
+            &to.key()
+        )?;
+
+        if let Some(programmable_transfer) = programmable_transfer {
+            // A programmable NFT (by the token standard of its metadata) is moved by the metadata program
+            programmable_transfer.transfer(
+                &escrow.to_account_info(),
+                &self.escrow_authority.to_account_info(),
+                &to.to_account_info(),
+                &receiver,
+                &mint.to_account_info(),
+                &self.party_one.to_account_info(),
+                &self.system_program.to_account_info(),
+                &token_program.to_account_info(),
+                &[&escrow_seed[..]]
+            )?;
+        } else {
+            transfer_checked_with_hook(
+                &token_program.to_account_info(),
+                &escrow.to_account_info(),
+                &mint.to_account_info(),
+                &to.to_account_info(),
+                &self.escrow_authority.to_account_info(),
+                remaining_accounts,
+                escrow.amount,
+                mint.decimals,
+                &[&escrow_seed[..]]
+            )?;
+        }
 
-        token::transfer(
-            transfer_context.with_signer(&[&escrow_seed[..]]),
-            amount
+        harvest_withheld_fees(
+            &token_program.to_account_info(),
+            &mint.to_account_info(),
+            &escrow.to_account_info()
         )?;
 
-        token::close_account(
+        let close_cpi_accounts = CloseAccount {
+            account: escrow.to_account_info(),
+            destination,
+            authority: self.escrow_authority.to_account_info()
+        };
+        let close_context = CpiContext::new(token_program.to_account_info(), close_cpi_accounts);
+
+        token_interface::close_account(
             close_context.with_signer(&[&escrow_seed[..]])
         )?;
             
-        Ok(())
+        Ok(remaining_accounts)
     }
 
     pub fn transfer_sol(&self,party: u8,amount: u64) -> Result<()> {
@@ -170,31 +228,20 @@ impl<'info> ExecuteTrade<'info> {
 
 }
 
-pub fn execute_trade_handler(ctx: Context<ExecuteTrade>) -> Result<()> {
-    let trade_details = &ctx.accounts.trade_details;
+pub fn execute_trade_handler<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteTrade<'info>>) -> Result<()> {
+    ctx.accounts.collection.check_operation(PausableOperation::ExecuteTrade)?;
 
-    let one_receive_address = if let Some(token_account) =
-    ctx.accounts.one_receive_address.as_ref() {
-        Some(token_account.key())
-    } else {
-        None
-    };
+    let trade_details = &ctx.accounts.trade_details;
 
     let confirm_status = trade_details.is_confirmed;
-    let two_mint_wrap = trade_details.two_mint;
 
     require_eq!(confirm_status,true,Errors::TradeNotAccepted);
 
-    if let Some(_mint) = two_mint_wrap {
-        match one_receive_address {
-            None => {
-                return Err(Errors::AccountNotProvided.into());
-            },
-            _ => ()
-        }
+    if trade_details.two_mint.is_some() && ctx.accounts.one_receive_address.is_none() {
+        return Err(Errors::AccountNotProvided.into());
     }
 
-    let trade_fees = ctx.accounts.collection.trade_fees;
+    let trade_fees = trade_details.trade_fee;
 
     if trade_fees > 0 {
         system_program::transfer(
@@ -203,12 +250,14 @@ pub fn execute_trade_handler(ctx: Context<ExecuteTrade>) -> Result<()> {
         )?;
     }
 
+    let mut remaining_accounts = ctx.remaining_accounts;
+
     if trade_details.spl_amount[0] > 0 {
-        ctx.accounts.transfer_spl(0, trade_details.spl_amount[0])?;
+        remaining_accounts = ctx.accounts.transfer_spl(0, remaining_accounts)?;
     }
 
     if trade_details.spl_amount[1] > 0 {
-        ctx.accounts.transfer_spl(1, trade_details.spl_amount[1])?;
+        ctx.accounts.transfer_spl(1, remaining_accounts)?;
 
     if trade_details.sol_amount[0] > 0 {
         ctx.accounts.transfer_sol(0, trade_details.sol_amount[0])?;
@@ -219,7 +268,9 @@ pub fn execute_trade_handler(ctx: Context<ExecuteTrade>) -> Result<()> {
     }
     }
 
-    
+    let collection = &mut ctx.accounts.collection;
+    collection.open_trades = collection.open_trades.checked_sub(1)
+        .ok_or(Errors::MathOverflow)?;
 
     Ok(())
 }
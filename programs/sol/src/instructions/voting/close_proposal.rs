@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::states::{Collection,Proposal};
+use crate::Errors;
+
+#[derive(Accounts)]
+pub struct CloseProposal<'info> {
+    /// CHECK: Deserialized in the handler (may be closed already)
+    #[account(address = proposal_details.collection)]
+    pub collection: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        has_one = creator,
+        close = creator
+    )]
+    pub proposal_details: Account<'info, Proposal>,
+
+    /// CHECK: Validated against the creator stored in the proposal
+    #[account(mut)]
+    pub creator: AccountInfo<'info>,
+
+    pub signer: Signer<'info>,
+}
+
+/// The proposal can be closed once it is executed or once its collection is closed
+pub fn close_proposal_handler(ctx: Context<CloseProposal>) -> Result<()> {
+    let collection = &ctx.accounts.collection;
+
+    if !collection.data_is_empty() {
+        let _collection: Account<Collection> = Account::try_from(collection)?;
+        require_eq!(ctx.accounts.proposal_details.is_active, false, Errors::VotingIsActive);
+    }
+
+    Ok(())
+}
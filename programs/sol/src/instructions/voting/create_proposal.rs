@@ -4,7 +4,7 @@ use anchor_spl::{
     metadata::MetadataAccount
 };
 
-use crate::states::{Collection,Proposal};
+use crate::states::{Collection,Proposal,PausableOperation};
 use crate::{Errors, MPL_TOKEN_METADATA_ID};
 
 #[derive(Accounts)]
@@ -60,6 +60,8 @@ pub fn create_proposal_handler(
     options: Vec<String>
 ) -> Result<()> {
 
+    ctx.accounts.collection_details.check_operation(PausableOperation::CreateProposal)?;
+
     let proposal_details = &mut ctx.accounts.proposal_details;
     let collection_details = &ctx.accounts.collection_details;
     let creator = ctx.accounts.signer.key();
@@ -79,10 +81,10 @@ pub fn create_proposal_handler(
     require_gte!(300, proposal_length, Errors::StringLengthExceeds);
     require_gt!(proposal_length, 0, Errors::BlankStringFound);
 
-    for i in 0..options.len() {
-        if options[i].len() > 25 {
+    for option in options.iter() {
+        if option.len() > 25 {
             return Err(Errors::StringLengthExceeds.into());
-        } else if options[i].len() < 1 {
+        } else if option.is_empty() {
             return Err(Errors::BlankStringFound.into());
         }
         votes.push(0);
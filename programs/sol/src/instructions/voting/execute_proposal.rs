@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::states::{Collection,Proposal};
+use crate::states::{Collection,Proposal,PausableOperation};
 use crate::Errors;
 
 #[derive(Accounts)]
@@ -18,6 +18,8 @@ pub struct ExecuteProposal<'info> {
 }
 
 pub fn execute_proposal_handler(ctx: Context<ExecuteProposal>) -> Result<()> {
+    ctx.accounts.collection.check_operation(PausableOperation::ExecuteProposal)?;
+
     let collection_details = &ctx.accounts.collection;
     let proposal_details = &mut ctx.accounts.proposal_details;
 
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::states::{Collection,Proposal};
+use crate::states::{Collection,Proposal,PausableOperation};
 use crate::{Errors,utils};
 
 #[derive(Accounts)]
@@ -19,10 +19,15 @@ pub struct GiveVote<'info> {
 }
 
 pub fn give_vote_handler(ctx: Context<GiveVote>,choice: u8) -> Result<()> {
+    ctx.accounts.collection.check_operation(PausableOperation::GiveVote)?;
+
     let signer = ctx.accounts.signer.key;
     let collection_details = & ctx.accounts.collection;
     let proposal_details = &mut ctx.accounts.proposal_details;
 
+    let is_voting_active = collection_details.is_voting;
+    require_eq!(is_voting_active,true, Errors::ModuleNotActive);
+
     let clock = Clock::get()?;
     let current_unix = clock.unix_timestamp;
 
@@ -1,7 +1,9 @@
 mod create_proposal;
 mod give_vote;
 mod execute_proposal;
+mod close_proposal;
 
 pub use create_proposal::*;
 pub use give_vote::*;
-pub use execute_proposal::*;
\ No newline at end of file
+pub use execute_proposal::*;
+pub use close_proposal::*;
\ No newline at end of file
@@ -5,12 +5,15 @@ pub mod states;
 pub mod utils;
 
 use instructions::*;
-use states::TradeType;
+use states::{TradeType,PausableOperation,CouncilActionType,StakeMode,EmissionModel,EmissionSchedule,LockTier};
 declare_id!("EtpeDoazsCWZU5Hp9kgyHmNKxvr7oHu8X82eTfR4W3oY");
 
 #[constant]
 pub const MPL_TOKEN_METADATA_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
 
+#[constant]
+pub const MPL_TOKEN_AUTH_RULES_ID: Pubkey = pubkey!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
+
 #[program]
 pub mod breeez {
     use super::*;
@@ -22,36 +25,262 @@ pub mod breeez {
         instructions::create_collection_handler(ctx, treasury)
     }
 
+    /// * Pauses or unpauses every state-changing instruction of the collection
+    /// * the operations in the allowlist (by default unstake_nft and cancel_trade) keep working while paused
+    /// * the allowlist is replaced only if provided
+    /// * with a council, requires an approved SetPause council action (closed to its creator)
+    pub fn set_pause(
+        ctx: Context<SetPause>,
+        is_paused: bool,
+        allowlist: Option<Vec<PausableOperation>>
+    ) -> Result<()> {
+        instructions::set_pause_handler(ctx, is_paused, allowlist)
+    }
+
+    /// * Queues the change of the treasury address (applied with apply_pending_change after the timelock)
+    pub fn update_treasury(ctx: Context<UpdateTreasury>, treasury: Pubkey) -> Result<()> {
+        instructions::update_treasury_handler(ctx, treasury)
+    }
+
+    /// * Creates the program owned treasury vault (PDA) of the collection
+    /// * the trade fees are sent to it once the treasury address is updated to the vault
+    /// * with a council, requires an approved InitTreasuryVault council action (closed to its creator)
+    pub fn init_treasury_vault(ctx: Context<InitTreasuryVault>) -> Result<()> {
+        instructions::init_treasury_vault_handler(ctx)
+    }
+
+    /// * Withdraws lamports from the treasury vault (keeping it rent exempt)
+    /// * with a council, the withdrawals are council actions instead
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        instructions::withdraw_treasury_handler(ctx, amount)
+    }
+
+    /// * Closes the collection account (fails while any NFT is staked or any trade is open)
+    /// * hands the mint authority of the collection token back to the admin (or empties and closes the reward vault)
+    /// * the co-rewards hand their mint authority back to their authority, or send it the tokens left in their vault
+    /// * empties and closes the treasury vault (if created) to the admin
+    /// * with a council, requires an approved CloseCollection council action (closed to its creator)
+    pub fn close_collection<'info>(ctx: Context<'_, '_, '_, 'info, CloseCollection<'info>>) -> Result<()> {
+        instructions::close_collection_handler(ctx)
+    }
+
+    /// * Proposes a new admin for the collection (the first step of the handover)
+    /// * the proposed admin must call accept_admin to take over
+    /// * with a council, requires an approved ProposeAdmin council action (closed to its creator)
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        instructions::propose_admin_handler(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::accept_admin_handler(ctx)
+    }
+
+    /// * Applies a queued configuration change once its timelock has passed
+    /// * can be called by anyone, the rent goes back to the creator of the change
+    pub fn apply_pending_change(ctx: Context<ApplyPendingChange>) -> Result<()> {
+        instructions::apply_pending_change_handler(ctx)
+    }
+
+    /// * Discards a queued change and returns the rent to its creator
+    /// * with a council, requires an approved CancelPendingChange council action (closed to its creator)
+    pub fn cancel_pending_change(ctx: Context<CancelPendingChange>) -> Result<()> {
+        instructions::cancel_pending_change_handler(ctx)
+    }
+
+    /// * Once configured, the module is added again with its current parameters (changed with edit_voting)
     pub fn add_voting(ctx: Context<AddVoting>, count: u64, duration: i64, quorum: u64) -> Result<()> {
         instructions::add_voting_handler(ctx, count, duration, quorum)
     }
 
+    /// * Queues the change (applied with apply_pending_change after the timelock)
     pub fn edit_voting(ctx: Context<EditVoting>, new_num: u64, edit_type: VotingEditType) -> Result<()> {
         instructions::edit_voting_handler(ctx, new_num, edit_type)
     }
 
+    /// * Deactivates the voting module
+    /// * active proposals can still be executed but no longer accept votes
+    pub fn remove_voting(ctx: Context<RemoveVoting>) -> Result<()> {
+        instructions::remove_voting_handler(ctx)
+    }
+
+    /// * Once configured, the module is added again with its current parameters (changed with edit_trade)
     pub fn add_trade(ctx: Context<AddTrade>, trade_fee: u64, duration: i64) -> Result<()> {
         instructions::add_trade_handler(ctx, trade_fee, duration)
     }
 
+    /// * Queues the change (applied with apply_pending_change after the timelock)
     pub fn edit_trade(ctx: Context<EditTrade>, new_num: u64, edit_type: TradeEditType) -> Result<()> {
         instructions::edit_trade_handler(ctx, new_num, edit_type)
     }
 
-    pub fn add_stake(ctx: Context<AddStake>, emission: u64) -> Result<()> {
-        instructions::add_stake_handler(ctx, emission)
+    /// * Deactivates the trade module
+    /// * open trades can still be executed or cancelled (without waiting for the trade duration)
+    pub fn remove_trade(ctx: Context<RemoveTrade>) -> Result<()> {
+        instructions::remove_trade_handler(ctx)
+    }
+
+    /// * The max supply is the hard cap on the token supply, it can only be lowered once set
+    /// * the rewards are paid from the emission budget (topped up with top_up_emission)
+    /// * the max supply is ignored if the rewards are paid from the reward vault (funded with fund_reward_vault)
+    /// * the stake mode selects between escrowing the NFTs and freezing them in the wallet of the holder
+    /// * the emission model selects between an emission per staked NFT and a pool shared pro-rata by the staked NFTs
+    /// * once configured, the module is added again with the same emission, stake mode and emission model
+    pub fn add_stake(
+        ctx: Context<AddStake>,
+        emission: u64,
+        max_supply: u64,
+        stake_mode: StakeMode,
+        emission_model: EmissionModel
+    ) -> Result<()> {
+        instructions::add_stake_handler(ctx, emission, max_supply, stake_mode, emission_model)
     }
 
+    /// * Queues the change (applied with apply_pending_change after the timelock)
+    /// * the rewards accrued before the change is applied are paid at the previous emission
     pub fn edit_stake(ctx: Context<EditStake>, emission: u64) -> Result<()> {
         instructions::edit_stake_handler(ctx, emission)
     }
 
-    pub fn add_token(ctx: Context<AddToken>) -> Result<()> {
-        instructions::add_token_handler(ctx)
+    /// * Deactivates the staking module
+    /// * staked NFTs can still be unstaked (with the accrued reward) but rewards can't be withdrawn otherwise
+    pub fn remove_stake(ctx: Context<RemoveStake>) -> Result<()> {
+        instructions::remove_stake_handler(ctx)
+    }
+
+    /// * Adds to the rewards left to be minted (the budget can't exceed the max supply)
+    /// * fails if the rewards are paid from the reward vault
+    pub fn top_up_emission(ctx: Context<TopUpEmission>, amount: u64) -> Result<()> {
+        instructions::top_up_emission_handler(ctx, amount)
+    }
+
+    /// * Queues the merkle root of the rarity table (applied with apply_pending_change after the timelock)
+    /// * the multiplier is proven and stored when the NFT is staked (the staked NFTs keep theirs)
+    /// * the NFTs earn the plain emission once the root is removed
+    pub fn set_rarity_root(ctx: Context<SetRarityRoot>, rarity_root: Option<[u8; 32]>) -> Result<()> {
+        instructions::set_rarity_root_handler(ctx, rarity_root)
+    }
+
+    /// * Queues the lock-up tiers of the collection (applied with apply_pending_change after the timelock)
+    /// * a tier locks the NFT for its duration and boosts its multiplier (in percent)
+    /// * an early exit tier allows unstaking before the unlock time but forfeits the accrued rewards
+    /// * the staked NFTs keep their tier and unlock time
+    pub fn set_lock_tiers(ctx: Context<SetLockTiers>, lock_tiers: Vec<LockTier>) -> Result<()> {
+        instructions::set_lock_tiers_handler(ctx, lock_tiers)
+    }
+
+    /// * Queues the emission schedule (applied with apply_pending_change after the timelock)
+    /// * nothing accrues before the start time or after the end time (if set)
+    /// * the emission steps down by the step rate every step duration (e.g. 50 halves it)
+    /// * the emission is constant once the schedule is removed
+    pub fn set_emission_schedule(
+        ctx: Context<SetEmissionSchedule>,
+        emission_schedule: Option<EmissionSchedule>
+    ) -> Result<()> {
+        instructions::set_emission_schedule_handler(ctx, emission_schedule)
+    }
+
+    /// * Queues the unbonding period (applied with apply_pending_change after the timelock)
+    /// * once set, unstake_nft stops the accrual and the NFT is returned with complete_unstake after the period
+    /// * applies to the NFTs unstaked afterwards (0 returns the NFTs on unstake)
+    pub fn set_unbonding_period(ctx: Context<SetUnbondingPeriod>, unbonding_period: i64) -> Result<()> {
+        instructions::set_unbonding_period_handler(ctx, unbonding_period)
+    }
+
+    /// * Sets an existing mint (of any decimals, Token or Token-2022) as the token of the collection
+    /// * the mint authority is handed over from the signer to the token-authority PDA
+    /// * creates the token metadata (name, symbol and URI) if provided, only for a Token mint
+    /// * with a council, requires an approved AddToken council action (closed to its creator)
+    pub fn add_token(ctx: Context<AddToken>, metadata: Option<TokenMetadataArgs>) -> Result<()> {
+        instructions::add_token_handler(ctx, metadata)
+    }
+
+    /// * Sets an existing mint as the token of the collection without its mint authority (e.g. a fixed supply)
+    /// * creates the reward vault (owned by the token-authority PDA), the staking rewards are transferred from it
+    /// * with a council, requires an approved AddRewardVault council action (closed to its creator)
+    pub fn add_reward_vault(ctx: Context<AddRewardVault>) -> Result<()> {
+        instructions::add_reward_vault_handler(ctx)
+    }
+
+    /// * Deposits the tokens of the admin into the reward vault and adds them to the emission budget
+    /// * the rewards are paid until the vault runs dry, a partial payout pays what is left
+    /// * with a council, requires an approved FundRewardVault council action (closed to its creator)
+    pub fn fund_reward_vault(ctx: Context<FundRewardVault>, amount: u64) -> Result<()> {
+        instructions::fund_reward_vault_handler(ctx, amount)
+    }
+
+    /// * Adds a reward token emitted alongside the collection token (max 2), e.g. the token of a partner
+    /// * the mint authority is handed over to the token-authority PDA, the budget caps what can be minted
+    /// * the co-rewards can't be removed, their emission can be stopped with edit_co_reward
+    /// * with a council, requires an approved AddCoReward council action (closed to its creator)
+    pub fn add_co_reward(ctx: Context<AddCoReward>, emission: u64, emission_budget: u64) -> Result<()> {
+        instructions::add_co_reward_handler(ctx, emission, emission_budget)
+    }
+
+    /// * Adds a reward token paid from a vault (funded with fund_co_reward)
+    /// * the authority receives the tokens left in the vault when the collection is closed
+    /// * starts without emission, the emission is set with edit_co_reward (after the timelock)
+    /// * with a council, requires an approved AddCoRewardVault council action (closed to its creator)
+    pub fn add_co_reward_vault(ctx: Context<AddCoRewardVault>, authority: Pubkey) -> Result<()> {
+        instructions::add_co_reward_vault_handler(ctx, authority)
+    }
+
+    /// * Deposits the tokens of the signer into the vault of the co-reward and adds them to its budget
+    pub fn fund_co_reward(ctx: Context<FundCoReward>, index: u8, amount: u64) -> Result<()> {
+        instructions::fund_co_reward_handler(ctx, index, amount)
+    }
+
+    /// * Queues the emission of the co-reward (applied with apply_pending_change after the timelock)
+    pub fn edit_co_reward(ctx: Context<EditCoReward>, index: u8, emission: u64) -> Result<()> {
+        instructions::edit_co_reward_handler(ctx, index, emission)
+    }
+
+    /// * Updates the metadata of the collection token (signed by the token-authority PDA), only for a Token mint
+    /// * with a council, requires an approved UpdateTokenMetadata council action (closed to its creator)
+    pub fn update_token_metadata(
+        ctx: Context<UpdateTokenMetadata>,
+        metadata: TokenMetadataArgs
+    ) -> Result<()> {
+        instructions::update_token_metadata_handler(ctx, metadata)
+    }
+    
+    /// * Registers a council of N members with an approval threshold of M
+    /// * once registered, the module and treasury changes can only be made through council actions
+    pub fn register_council(
+        ctx: Context<RegisterCouncil>,
+        members: Vec<Pubkey>,
+        threshold: u8
+    ) -> Result<()> {
+        instructions::register_council_handler(ctx, members, threshold)
+    }
+
+    /// * Creates a pending council action approved by the signer
+    /// * other members can approve in the same transaction by signing as remaining accounts
+    pub fn create_council_action(
+        ctx: Context<CreateCouncilAction>,
+        action: CouncilActionType
+    ) -> Result<()> {
+        instructions::create_council_action_handler(ctx, action)
+    }
+
+    pub fn approve_council_action(ctx: Context<ApproveCouncilAction>) -> Result<()> {
+        instructions::approve_council_action_handler(ctx)
+    }
+
+    /// * Applies the action once the threshold is reached (can be called by anyone)
+    /// * the edits and treasury changes are queued in the pending change account instead
+    /// * the treasury withdrawals move the lamports from the treasury vault to the destination right away
+    /// * closes the action account and returns the rent to its creator
+    pub fn execute_council_action(ctx: Context<ExecuteCouncilAction>) -> Result<()> {
+        instructions::execute_council_action_handler(ctx)
     }
     
-    pub fn create_trade(
-        ctx: Context<CreateTrade>,
+    /// * Escrows the SOL and/or SPL of the first party
+    /// * the escrowed SPL amount is what the escrow received (net of the Token-2022 transfer fee)
+    /// * the accounts of the transfer hook (if any) are passed as the remaining accounts
+    /// * a programmable NFT is moved by the metadata program, its accounts are passed as the remaining accounts instead
+    ///   (starting with its metadata, the token standard of which marks the NFT as programmable)
+    pub fn create_trade<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateTrade<'info>>,
         sol_amount: u64,
         spl_amount: u64,
         trade_type: TradeType
@@ -59,12 +288,20 @@ pub mod breeez {
         instructions::create_trade_handler(ctx, sol_amount, spl_amount, trade_type)
     }
 
-    pub fn cancel_trade(ctx: Context<CancelTrade>) -> Result<()> {
+    /// * Returns the whole balance of the escrows and closes them
+    /// * the accounts of the transfer hooks (if any) of both legs are passed as the remaining accounts
+    /// * the accounts of the programmable NFT legs come first (in the order of the legs)
+    pub fn cancel_trade<'info>(ctx: Context<'_, '_, '_, 'info, CancelTrade<'info>>) -> Result<()> {
         instructions::cancel_trade_handler(ctx)
     }
 
-    pub fn accept_trade(
-        ctx: Context<AcceptTrade>,
+    /// * Escrows the SOL and/or SPL of the second party
+    /// * the escrowed SPL amount is what the escrow received (net of the Token-2022 transfer fee)
+    /// * the accounts of the transfer hook (if any) are passed as the remaining accounts
+    /// * a programmable NFT is moved by the metadata program, its accounts are passed as the remaining accounts instead
+    ///   (starting with its metadata, the token standard of which marks the NFT as programmable)
+    pub fn accept_trade<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptTrade<'info>>,
         sol_amount: u64,
         spl_amount: u64,
         trade_type: TradeType
@@ -72,7 +309,12 @@ pub mod breeez {
         instructions::accept_trade_handler(ctx, sol_amount, spl_amount, trade_type)
     }
 
-    pub fn execute_trade(ctx: Context<ExecuteTrade>) -> Result<()> {
+    /// * Sends the whole balance of the escrows to the other party and closes them
+    /// * charges the trade fee fixed when the trade was accepted
+    /// * the withheld transfer fees are moved to the mint so the escrows can be closed
+    /// * the accounts of the transfer hooks (if any) of both legs are passed as the remaining accounts
+    /// * the accounts of the programmable NFT legs come first (in the order of the legs)
+    pub fn execute_trade<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteTrade<'info>>) -> Result<()> {
         instructions::execute_trade_handler(ctx)
     }
 
@@ -92,22 +334,126 @@ pub mod breeez {
         instructions::execute_proposal_handler(ctx)
     }
 
-    pub fn stake_nft(ctx: Context<StakeNft>) -> Result<()> {
-        instructions::stake_nft_handler(ctx)
+    /// * Closes an executed proposal (or any proposal of a closed collection)
+    /// * can be called by anyone, the rent goes back to the creator of the proposal
+    pub fn close_proposal(ctx: Context<CloseProposal>) -> Result<()> {
+        instructions::close_proposal_handler(ctx)
     }
 
-    pub fn unstake_nft(ctx: Context<UnstakeNft>) -> Result<()> {
-        instructions::unstake_nft_handler(ctx)
+    /// * Escrows the NFT or freezes it in the token account of the holder (the stake mode of the collection)
+    /// * the frozen NFT stays in the wallet (and can still back the identity)
+    /// * the programmable NFTs are always locked in the wallet by the staking delegate
+    /// * the rarity multiplier of the NFT is proven against the rarity root (if set)
+    /// * the NFT is locked in the lock tier (if picked) until its lock-up duration has passed
+    pub fn stake_nft(
+        ctx: Context<StakeNft>,
+        rarity: Option<RarityProof>,
+        lock_tier: Option<u8>
+    ) -> Result<()> {
+        instructions::stake_nft_handler(ctx, rarity, lock_tier)
+    }
+
+    /// * Returns the NFT from the escrow, or thaws it and revokes the delegate (as it was staked)
+    /// * the frozen NFT passes its metadata and master edition
+    /// * the NFT goes back to (or is thawed in) the token account it was staked from
+    /// * pays the accrued reward only up to the emission budget and the max supply left
+    /// * fails if the payout runs short, unless forfeit_unpaid is set (the rest is forfeited with the stake)
+    /// * fails before the unlock time, unless the tier allows an early exit (the reward is forfeited)
+    /// * with an unbonding period, the NFT stops earning and is kept until complete_unstake
+    ///   (the rest of a short payout is kept on the stake and paid by complete_unstake)
+    /// * the co-rewards are paid from their accounts in the remaining accounts
+    pub fn unstake_nft<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeNft<'info>>, forfeit_unpaid: bool) -> Result<()> {
+        instructions::unstake_nft_handler(ctx, forfeit_unpaid)
+    }
+
+    /// * Returns the unbonding NFT once the unbonding period has passed (the same accounts as unstake_nft)
+    /// * pays the rest of a short payout kept on unstake, fails if it still runs short unless forfeit_unpaid is set
+    pub fn complete_unstake<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeNft<'info>>, forfeit_unpaid: bool) -> Result<()> {
+        instructions::complete_unstake_handler(ctx, forfeit_unpaid)
     }
 
-    pub fn withdraw_tokens(ctx: Context<WithdrawTokens>) -> Result<()> {
+    /// * Pays the accrued reward only up to the emission budget and the max supply left
+    /// * fails if nothing is left, the rest of a partial payout is kept in the stake for a later claim
+    /// * fails before the unlock time for an early exit tier
+    /// * pays the co-rewards too, each co-reward passes its mint, token program, token account
+    ///   and vault (if paid from a vault) as the remaining accounts
+    pub fn withdraw_tokens<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawTokens<'info>>) -> Result<()> {
         instructions::withdraw_tokens_handler(ctx)
     }
 
+    /// * Stakes up to 8 NFTs in the stake mode of the collection (escrow or freeze)
+    /// * each NFT passes its mint, token account, metadata, stake PDA and the nft-escrow PDA
+    ///   (escrow mode) or the master edition (freeze mode) as the remaining accounts
+    /// * the programmable NFTs are staked one by one with stake_nft
+    /// * the rarity proofs (if the rarity root is set) are in the order of the NFTs
+    /// * all the NFTs are locked in the lock tier (if picked)
+    pub fn stake_many<'info>(
+        ctx: Context<'_, '_, '_, 'info, StakeMany<'info>>,
+        rarities: Vec<RarityProof>,
+        lock_tier: Option<u8>
+    ) -> Result<()> {
+        instructions::stake_many_handler(ctx, rarities, lock_tier)
+    }
+
+    /// * Unstakes up to 8 NFTs, the rewards of all of them are paid at once
+    /// * each NFT passes its mint, token account, stake PDA, the nft-escrow PDA (escrow mode)
+    ///   or the master edition (freeze mode) and its metadata as the remaining accounts
+    /// * the programmable NFTs are unstaked one by one with unstake_nft
+    /// * with an unbonding period, the NFTs stop earning and are returned one by one with complete_unstake
+    /// * the rest of a short payout is kept on the first unbonding stake, or fails unless forfeit_unpaid is set
+    /// * the accounts of the co-rewards (as in withdraw_tokens) follow the NFTs
+    pub fn unstake_many<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeMany<'info>>, forfeit_unpaid: bool) -> Result<()> {
+        instructions::unstake_many_handler(ctx, forfeit_unpaid)
+    }
+
+    /// * Claims the rewards of up to 20 stakes (passed as the remaining accounts) at once
+    /// * each stake can be passed only once
+    /// * the payout is capped as in withdraw_tokens
+    /// * the accounts of the co-rewards (as in withdraw_tokens) follow the stakes
+    pub fn claim_all<'info>(ctx: Context<'_, '_, '_, 'info, ClaimAll<'info>>) -> Result<()> {
+        instructions::claim_all_handler(ctx)
+    }
+
     pub fn create_identity(ctx: Context<CreateIdentity>,username: String) -> Result<()> {
         instructions::create_identity_handler(ctx, username)
     }
 
+    /// * Upgrades a collection account created before the version field to the current layout
+    /// * the update authority of the collection signs and becomes its admin
+    pub fn migrate_collection(ctx: Context<MigrateCollection>) -> Result<()> {
+        instructions::migrate_collection_handler(ctx)
+    }
+
+    /// * Upgrades the account to the current layout (can be called by anyone, the signer pays the extra rent)
+    pub fn migrate_stake(ctx: Context<MigrateStake>) -> Result<()> {
+        instructions::migrate_stake_handler(ctx)
+    }
+
+    pub fn migrate_trade(ctx: Context<MigrateTrade>) -> Result<()> {
+        instructions::migrate_trade_handler(ctx)
+    }
+
+    pub fn migrate_proposal(ctx: Context<MigrateProposal>) -> Result<()> {
+        instructions::migrate_proposal_handler(ctx)
+    }
+
+    /// * Returns the NFT of a stake left in the original layout once its collection is closed
+    ///   (the accrued rewards are forfeited, can be called by anyone)
+    pub fn release_legacy_stake(ctx: Context<ReleaseLegacyStake>) -> Result<()> {
+        instructions::release_legacy_stake_handler(ctx)
+    }
+
+    /// * Returns the escrowed tokens and SOL of a trade left in the original layout once its collection is closed
+    pub fn release_legacy_trade(ctx: Context<ReleaseLegacyTrade>) -> Result<()> {
+        instructions::release_legacy_trade_handler(ctx)
+    }
+
+    /// * Closes the identity and username accounts of a closed collection
+    /// * can be called by anyone, the rent goes back to the holder
+    pub fn close_identity(ctx: Context<CloseIdentity>) -> Result<()> {
+        instructions::close_identity_handler(ctx)
+    }
+
 }
 
 #[error_code]
@@ -215,5 +561,119 @@ pub enum Errors {
     TokenAlreadyExists,
 
     #[msg("The signer is not the owner of the token account")]
-    InvalidOwner
+    InvalidOwner,
+
+    #[msg("The signer is not the admin of the collection")]
+    NotAdmin,
+
+    #[msg("The signer is not the pending admin of the collection")]
+    NotPendingAdmin,
+
+    #[msg("The collection is paused")]
+    CollectionPaused,
+
+    #[msg("The collection is managed by a council")]
+    CouncilRequired,
+
+    #[msg("The council is already registered for this collection")]
+    CouncilAlreadyRegistered,
+
+    #[msg("The council members or threshold are invalid")]
+    InvalidCouncil,
+
+    #[msg("The signer is not a member of the council")]
+    NotCouncilMember,
+
+    #[msg("The member has already approved the action")]
+    AlreadyApproved,
+
+    #[msg("The action doesn't have enough approvals")]
+    ThresholdNotReached,
+
+    #[msg("The timelock of the change has not passed")]
+    TimelockNotExpired,
+
+    #[msg("The collection still has staked NFTs")]
+    StakesOpen,
+
+    #[msg("The collection still has open trades")]
+    TradesOpen,
+
+    #[msg("The collection is not closed")]
+    CollectionNotClosed,
+
+    #[msg("The treasury address is invalid")]
+    InvalidTreasury,
+
+    #[msg("The account is already migrated to the current version")]
+    AlreadyMigrated,
+
+    #[msg("The amount exceeds the max supply of the token")]
+    MaxSupplyExceeded,
+
+    #[msg("The emission budget of the collection is exhausted")]
+    EmissionBudgetExhausted,
+
+    #[msg("The calculation overflowed")]
+    MathOverflow,
+
+    #[msg("The stake mode can't be selected for the collection")]
+    InvalidStakeMode,
+
+    #[msg("The batch is empty, too large or its accounts are incomplete")]
+    InvalidBatchSize,
+
+    #[msg("The programmable NFTs can't be staked or unstaked in a batch")]
+    ProgrammableNftNotSupported,
+
+    #[msg("The stake, escrow or edition PDA doesn't match with the token mint")]
+    WrongStakeAccount,
+
+    #[msg("The rarity multiplier of the NFT is not proven by the rarity root")]
+    InvalidRarityProof,
+
+    #[msg("The lock tier doesn't exist or is invalid")]
+    InvalidLockTier,
+
+    #[msg("The NFT is locked until the unlock time of its tier")]
+    StakeLocked,
+
+    #[msg("The rewards are paid from the reward vault, fund the vault instead")]
+    RewardVaultMode,
+
+    #[msg("The collection has no reward vault or the reward vault is incorrect")]
+    RewardVaultNotFound,
+
+    #[msg("The emission schedule is invalid")]
+    InvalidEmissionSchedule,
+
+    #[msg("The unbonding period can't be negative")]
+    InvalidUnbondingPeriod,
+
+    #[msg("The NFT is unbonding, it is returned with complete_unstake after the unbonding period")]
+    StakeUnbonding,
+
+    #[msg("The NFT isn't unbonding")]
+    StakeNotUnbonding,
+
+    #[msg("The co-reward doesn't exist or is invalid")]
+    InvalidCoReward,
+
+    #[msg("The accounts or the instruction don't match the council action")]
+    CouncilActionMismatch,
+
+    #[msg("The same account is passed more than once")]
+    DuplicateAccount,
+
+    #[msg("The module was configured before, its parameters are changed through the timelocked edits")]
+    ModuleAlreadyConfigured,
+
+    #[msg("The rewards can't be paid in full, unstake with forfeit_unpaid to forfeit the rest")]
+    RewardsUnpaid,
+
+    #[msg("The token metadata is only supported for the mints of the original token program")]
+    MetadataNotSupported,
+
+    #[msg("The accounts of the programmable NFT are incorrect")]
+    InvalidProgrammableAccounts
 }
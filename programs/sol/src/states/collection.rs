@@ -1,13 +1,26 @@
 use anchor_lang::prelude::*;
+use crate::{Errors, ID, utils};
+use crate::states::{ConfigChange,StakeMode};
+use crate::instructions::{TradeEditType,VotingEditType,RarityProof};
 
 #[account]
 pub struct Collection {
+    /// The version of the account layout
+    pub version: u8,
     /// The verified key of the collection
     pub verified_collection_key: Pubkey,
+    /// The admin authorised to configure the collection
+    pub admin: Pubkey,
+    /// The admin proposed by the current admin (yet to accept)
+    pub pending_admin: Option<Pubkey>,
     /// The treasury address (to receive trade fee)
     pub treasury_address: Pubkey,
     /// The mint address of the token associated with the collection
     pub token_mint: Option<Pubkey>,
+    /// The decimals of the token associated with the collection
+    pub token_decimals: u8,
+    /// The vault paying the staking rewards (if set, the rewards are transferred from it instead of minted)
+    pub reward_vault: Option<Pubkey>,
     /// Is Staking Module active
     pub is_staking: bool,
     /// Is Voting Module active
@@ -26,21 +39,77 @@ pub struct Collection {
     pub trade_duration: i64,
     /// The fees for the trade (to be sent to treasury address - in Lamports)
     pub trade_fees: u64,
-    /// The emission of tokens per hour of staking
-    pub emission: u64
+    /// The emission of tokens per hour of staking, per NFT or for the whole pool (with 9 decimals, scaled to the token decimals)
+    pub emission: u64,
+    /// Whether the emission is earned by each staked NFT or shared by all of them
+    pub emission_model: EmissionModel,
+    /// The schedule of the emission (start, end and step-downs), the emission is constant if not set
+    pub emission_schedule: Option<EmissionSchedule>,
+    /// How the staked NFTs are held (applies to the NFTs staked afterwards)
+    pub stake_mode: StakeMode,
+    /// The hard cap on the supply of the token (the rewards are never minted above it)
+    pub max_supply: u64,
+    /// The rewards left to be minted (topped up by the admin, never above the max supply)
+    pub emission_budget: u64,
+    /// The reward accrued per staked NFT (in token units, fixed point)
+    pub reward_index: u128,
+    /// The time up to which the reward index is accrued
+    pub last_update_time: i64,
+    /// The rewards emitted alongside the collection token, e.g. the token of a partner (max 2)
+    pub co_rewards: Vec<CoReward>,
+    /// The merkle root of the rarity table, (mint, multiplier) leaves (the NFTs earn the plain emission if not set)
+    pub rarity_root: Option<[u8; 32]>,
+    /// The lock-up tiers which can be picked when staking (max 4)
+    pub lock_tiers: Vec<LockTier>,
+    /// The cooldown (in seconds) between unstaking and the return of the NFT (0 for none)
+    pub unbonding_period: i64,
+    /// The number of NFTs currently staked
+    pub open_stakes: u64,
+    /// The sum of the multipliers of the staked NFTs (their shares of the emission pool)
+    pub staked_weight: u64,
+    /// The number of trades currently open (created but not executed or cancelled)
+    pub open_trades: u64,
+    /// The council whose approval is required to configure the collection (if registered)
+    pub council: Option<Pubkey>,
+    /// The bump of the token-authority PDA (mint authority of the collection token)
+    pub token_authority_bump: u8,
+    /// The bump of the nft-authority PDA (owner of the staked NFTs)
+    pub nft_authority_bump: u8,
+    /// The bump of the escrow PDA (owner of the trade escrows)
+    pub escrow_authority_bump: u8,
+    /// Whether the collection is paused (emergency circuit breaker)
+    pub is_paused: bool,
+    /// The bitmask of the operations allowed while the collection is paused
+    pub pause_allowlist: u16
 }
 
 impl Collection {
-    pub const LEN: usize = 8 + 32 + 32 + 1 + 32 + 1 + 1 + 1 + 1 + 8 + 8 + 8 + 8 + 8 + 8; 
+    /// The delay (in seconds) before a queued configuration change can be applied
+    pub const TIMELOCK: i64 = 86400;
+
+    pub const VERSION: u8 = 1;
+
+    pub const MAX_LOCK_TIERS: usize = 4;
+
+    pub const MAX_CO_REWARDS: usize = 2;
+
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 1 + 32 + 32 + 1 + 32 + 1 + 1 + 32 + 1 + 1 + 1 + 1 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + EmissionSchedule::LEN + 1 + 8 + 8 + 16 + 8 + 4 + CoReward::LEN * Collection::MAX_CO_REWARDS + 1 + 32 + 4 + LockTier::LEN * Collection::MAX_LOCK_TIERS + 8 + 8 + 8 + 8 + 1 + 32 + 1 + 1 + 1 + 1 + 2; 
 
     pub fn new(
         verified_collection_key: Pubkey,
-        treasury_address: Pubkey
+        admin: Pubkey,
+        treasury_address: Pubkey,
+        authority_bumps: AuthorityBumps
     ) -> Self {
         Self { 
+            version: Collection::VERSION,
             verified_collection_key, 
+            admin,
+            pending_admin: None,
             treasury_address, 
             token_mint: None, 
+            token_decimals: 0,
+            reward_vault: None,
             is_staking: false, 
             is_voting: false, 
             is_trade: false, 
@@ -50,7 +119,924 @@ impl Collection {
             quorum: 0,
             trade_duration: 0,
             trade_fees: 0,
-            emission: 0
+            emission: 0,
+            emission_model: EmissionModel::PerNft,
+            emission_schedule: None,
+            stake_mode: StakeMode::Escrow,
+            max_supply: 0,
+            emission_budget: 0,
+            reward_index: 0,
+            last_update_time: 0,
+            co_rewards: Vec::new(),
+            rarity_root: None,
+            lock_tiers: Vec::new(),
+            unbonding_period: 0,
+            open_stakes: 0,
+            staked_weight: 0,
+            open_trades: 0,
+            council: None,
+            token_authority_bump: authority_bumps.token_authority,
+            nft_authority_bump: authority_bumps.nft_authority,
+            escrow_authority_bump: authority_bumps.escrow_authority,
+            is_paused: false,
+            pause_allowlist: PausableOperation::UnstakeNft.flag() |
+                PausableOperation::CancelTrade.flag()
+        }
+    }
+
+    /// Upgrades the original (unversioned) layout, the admin is the update authority of the collection
+    pub fn from_v0(legacy: CollectionV0, admin: Pubkey, authority_bumps: AuthorityBumps) -> Self {
+        let mut collection = Collection::new(
+            legacy.verified_collection_key,
+            admin,
+            legacy.treasury_address,
+            authority_bumps
+        );
+
+        collection.token_mint = legacy.token_mint;
+        // The original program always created the token with 9 decimals
+        collection.token_decimals = 9;
+        collection.is_staking = legacy.is_staking;
+        collection.is_voting = legacy.is_voting;
+        collection.is_trade = legacy.is_trade;
+        collection.is_community = legacy.is_community;
+        collection.count = legacy.count;
+        collection.vote_duration = legacy.vote_duration;
+        collection.quorum = legacy.quorum;
+        collection.trade_duration = legacy.trade_duration;
+        collection.trade_fees = legacy.trade_fees;
+        collection.emission = legacy.emission;
+        // The original program minted the rewards without limit
+        collection.max_supply = u64::MAX;
+        collection.emission_budget = u64::MAX;
+        collection.last_update_time = Clock::get().unwrap().unix_timestamp;
+        collection
+    }
+
+    pub fn add_voting(&mut self, count: u64, duration: i64, quorum: u64) -> Result<()> {
+        require_eq!(self.is_voting, false, Errors::ModuleAlreadyAdded);
+
+        require_gt!(count, 0, Errors::ZeroValue);
+        require_gt!(duration, 0, Errors::ZeroValue);
+        require_gt!(quorum, 0, Errors::ZeroValue);
+
+        require_gte!(count, quorum, Errors::InvalidQuorum);
+
+        // Once configured, the parameters are only changed through the timelock (removing the module doesn't reset them)
+        if self.vote_duration > 0 {
+            require!(
+                count == self.count && duration == self.vote_duration && quorum == self.quorum,
+                Errors::ModuleAlreadyConfigured
+            );
+        }
+
+        self.is_voting = true;
+        self.quorum = quorum;
+        self.count = count;
+        self.vote_duration = duration;
+        Ok(())
+    }
+
+    pub fn edit_voting(&mut self, new_num: u64, edit_type: &VotingEditType) -> Result<()> {
+        require_gt!(new_num, 0, Errors::ZeroValue);
+
+        match edit_type {
+            VotingEditType::Count => {
+                require_gte!(new_num, self.quorum, Errors::InvalidQuorum);
+                self.count = new_num;
+            },
+            VotingEditType::Duration => {
+                self.vote_duration = new_num as i64;
+            },
+            VotingEditType::Quorum => {
+                require_gte!(self.count, new_num, Errors::InvalidQuorum);
+                self.quorum = new_num;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove_voting(&mut self) -> Result<()> {
+        require_eq!(self.is_voting, true, Errors::ModuleNotActive);
+
+        self.is_voting = false;
+        Ok(())
+    }
+
+    pub fn add_trade(&mut self, trade_fee: u64, duration: i64) -> Result<()> {
+        require_eq!(self.is_trade, false, Errors::ModuleAlreadyAdded);
+        
+        require_gt!(duration, 0, Errors::ZeroValue);
+
+        // Once configured, the parameters are only changed through the timelock (removing the module doesn't reset them)
+        if self.trade_duration > 0 {
+            require!(
+                trade_fee == self.trade_fees && duration == self.trade_duration,
+                Errors::ModuleAlreadyConfigured
+            );
+        }
+
+        self.is_trade = true;
+        self.trade_duration = duration;
+        self.trade_fees = trade_fee;
+        Ok(())
+    }
+
+    pub fn edit_trade(&mut self, new_num: u64, edit_type: &TradeEditType) -> Result<()> {
+        match edit_type {
+            TradeEditType::Fee => {
+                self.trade_fees = new_num;
+            },
+            TradeEditType::Duration => {
+                require_gt!(new_num, 0, Errors::ZeroValue);
+                self.trade_duration = new_num as i64;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove_trade(&mut self) -> Result<()> {
+        require_eq!(self.is_trade, true, Errors::ModuleNotActive);
+
+        self.is_trade = false;
+        Ok(())
+    }
+
+    pub fn add_stake(
+        &mut self,
+        emission: u64,
+        max_supply: u64,
+        stake_mode: StakeMode,
+        emission_model: EmissionModel
+    ) -> Result<()> {
+        require_eq!(self.is_staking, false, Errors::ModuleAlreadyAdded);
+
+        if self.token_mint.is_none() {
+            return Err(Errors::TokenNotFound.into());
+        }
+
+        require_gt!(emission, 0, Errors::ZeroValue);
+        require_gt!(max_supply, 0, Errors::ZeroValue);
+
+        if stake_mode == StakeMode::Lock {
+            return Err(Errors::InvalidStakeMode.into());
+        }
+
+        // Once configured, the emission is only changed through the timelock (removing the module doesn't reset it)
+        if self.emission > 0 {
+            require!(
+                emission == self.emission && stake_mode == self.stake_mode && emission_model == self.emission_model,
+                Errors::ModuleAlreadyConfigured
+            );
         }
+
+        // Once set, the max supply can only be lowered (when the module is added again)
+        if self.max_supply > 0 {
+            require_gte!(self.max_supply, max_supply, Errors::MaxSupplyExceeded);
+        }
+
+        // The rewards accrued so far are kept at the previous emission
+        self.update_reward_index()?;
+
+        self.is_staking = true;
+        self.emission = emission;
+        self.emission_model = emission_model;
+        self.stake_mode = stake_mode;
+        self.max_supply = max_supply;
+
+        // The budget of the reward vault is what was deposited into it
+        if self.reward_vault.is_none() {
+            self.emission_budget = self.emission_budget.min(max_supply);
+        }
+        Ok(())
     }
-}
\ No newline at end of file
+
+    pub fn edit_stake(&mut self, emission: u64) -> Result<()> {
+        require_gt!(emission, 0, Errors::ZeroValue);
+
+        // The rewards accrued so far are kept at the previous emission
+        self.update_reward_index()?;
+
+        self.emission = emission;
+        Ok(())
+    }
+
+    /// Accrues the reward indexes (of the collection token and the co-rewards) up to the current time
+    /// * the index is the reward of an NFT of the base multiplier
+    pub fn update_reward_index(&mut self) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+
+        let accrued = self.accrued_index(current_time, self.emission, self.token_decimals)?;
+        self.reward_index = self.reward_index
+            .checked_add(accrued)
+            .ok_or(Errors::MathOverflow)?;
+
+        for i in 0 .. self.co_rewards.len() {
+            let co_reward = self.co_rewards[i];
+            let accrued = self.accrued_index(current_time, co_reward.emission, co_reward.decimals)?;
+
+            self.co_rewards[i].reward_index = co_reward.reward_index
+                .checked_add(accrued)
+                .ok_or(Errors::MathOverflow)?;
+        }
+
+        self.last_update_time = current_time;
+        Ok(())
+    }
+
+    /// Returns the reward index accrued since the last update at the emission (following the schedule and the model)
+    fn accrued_index(&self, current_time: i64, emission: u64, decimals: u8) -> Result<u128> {
+        let elapsed = current_time
+            .checked_sub(self.last_update_time)
+            .ok_or(Errors::MathOverflow)?;
+        let emitted = match self.emission_schedule.as_ref() {
+            Some(schedule) => {
+                schedule.reward_index(self.last_update_time, current_time, emission, decimals)?
+            },
+            None => utils::calc_reward_index(elapsed, emission, decimals)?
+        };
+
+        let accrued = match self.emission_model {
+            EmissionModel::PerNft => emitted,
+            // The pool is split by the multipliers, nothing accrues while nothing is staked
+            EmissionModel::Pool => {
+                if self.staked_weight == 0 {
+                    0
+                } else {
+                    emitted
+                        .checked_mul(utils::BASE_MULTIPLIER as u128)
+                        .ok_or(Errors::MathOverflow)? / self.staked_weight as u128
+                }
+            }
+        };
+        Ok(accrued)
+    }
+
+    pub fn top_up_emission(&mut self, amount: u64) -> Result<()> {
+        require_gt!(amount, 0, Errors::ZeroValue);
+
+        if self.reward_vault.is_some() {
+            return Err(Errors::RewardVaultMode.into());
+        }
+
+        let emission_budget = self.emission_budget.checked_add(amount)
+            .ok_or(Errors::MaxSupplyExceeded)?;
+        require_gte!(self.max_supply, emission_budget, Errors::MaxSupplyExceeded);
+
+        self.emission_budget = emission_budget;
+        Ok(())
+    }
+
+    /// Sets an existing mint as the token of the collection, the rewards are paid from the vault
+    pub fn add_reward_vault(&mut self, token_mint: Pubkey, token_decimals: u8, reward_vault: Pubkey) -> Result<()> {
+        if self.token_mint.is_some() {
+            return Err(Errors::TokenAlreadyExists.into());
+        }
+
+        self.token_mint = Some(token_mint);
+        self.token_decimals = token_decimals;
+        self.reward_vault = Some(reward_vault);
+        // Only the deposits into the vault can be paid
+        self.emission_budget = 0;
+        Ok(())
+    }
+
+    /// Adds the amount deposited into the reward vault to the emission budget
+    pub fn fund_reward_vault(&mut self, amount: u64) -> Result<()> {
+        require_gt!(amount, 0, Errors::ZeroValue);
+
+        if self.reward_vault.is_none() {
+            return Err(Errors::RewardVaultNotFound.into());
+        }
+
+        self.emission_budget = self.emission_budget.checked_add(amount)
+            .ok_or(Errors::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Returns the rewards which can be paid right now
+    /// * the balance of the reward vault (if set), otherwise the room left below the max supply
+    pub fn reward_available(&self, supply: u64, vault_balance: Option<u64>) -> Result<u64> {
+        match (self.reward_vault, vault_balance) {
+            (None, _) => Ok(self.max_supply.saturating_sub(supply)),
+            (Some(_), Some(vault_balance)) => Ok(vault_balance),
+            (Some(_), None) => Err(Errors::AccountNotProvided.into())
+        }
+    }
+
+    /// Deducts the reward from the emission budget and returns the amount which can be paid
+    /// * the amount is capped by the budget left and by the rewards available (see reward_available)
+    /// * the claims keep the rest in the stake (see Stake::keep_unpaid)
+    pub fn claim_emission(&mut self, reward: u64, available: u64) -> u64 {
+        let amount = reward
+            .min(self.emission_budget)
+            .min(available);
+
+        self.emission_budget -= amount;
+        amount
+    }
+
+    /// Adds a reward emitted alongside the collection token (it can't be removed, its emission can be stopped)
+    pub fn add_co_reward(&mut self, co_reward: CoReward) -> Result<()> {
+        require_gt!(Collection::MAX_CO_REWARDS, self.co_rewards.len(), Errors::InvalidCoReward);
+
+        if self.token_mint.is_none() {
+            return Err(Errors::TokenNotFound.into());
+        }
+
+        // Each mint is paid once
+        if self.token_mint == Some(co_reward.mint) ||
+        self.co_rewards.iter().any(|added| added.mint == co_reward.mint) {
+            return Err(Errors::InvalidCoReward.into());
+        }
+
+        // The other rewards accrued so far are kept
+        self.update_reward_index()?;
+
+        self.co_rewards.push(co_reward);
+        Ok(())
+    }
+
+    pub fn co_reward(&mut self, index: u8) -> Result<&mut CoReward> {
+        self.co_rewards
+            .get_mut(index as usize)
+            .ok_or(Errors::InvalidCoReward.into())
+    }
+
+    /// Returns the reward indexes of the co-rewards (the checkpoints of a new stake)
+    pub fn co_reward_indexes(&self) -> [u128; Collection::MAX_CO_REWARDS] {
+        let mut co_reward_indexes = [0; Collection::MAX_CO_REWARDS];
+        for (i, co_reward) in self.co_rewards.iter().enumerate() {
+            co_reward_indexes[i] = co_reward.reward_index;
+        }
+        co_reward_indexes
+    }
+
+    pub fn edit_co_reward(&mut self, index: u8, emission: u64) -> Result<()> {
+        self.co_reward(index)?;
+
+        // The rewards accrued so far are kept at the previous emission
+        self.update_reward_index()?;
+
+        self.co_reward(index)?.emission = emission;
+        Ok(())
+    }
+
+    /// Returns the rarity multiplier of the NFT, proven against the rarity root
+    /// * the proof isn't read if the collection has no rarity table
+    pub fn rarity_multiplier(&self, mint: &Pubkey, rarity: Option<&RarityProof>) -> Result<u16> {
+        match (self.rarity_root.as_ref(), rarity) {
+            (None, _) => Ok(utils::BASE_MULTIPLIER),
+            (Some(root), Some(rarity)) => {
+                require_gt!(rarity.multiplier, 0, Errors::ZeroValue);
+
+                utils::verify_rarity(root, mint, rarity.multiplier, &rarity.proof)?;
+                Ok(rarity.multiplier)
+            },
+            (Some(_), None) => {
+                Err(Errors::InvalidRarityProof.into())
+            }
+        }
+    }
+
+    pub fn lock_tier(&self, lock_tier: u8) -> Result<LockTier> {
+        self.lock_tiers
+            .get(lock_tier as usize)
+            .copied()
+            .ok_or(Errors::InvalidLockTier.into())
+    }
+
+    pub fn set_lock_tiers(&mut self, lock_tiers: &[LockTier]) -> Result<()> {
+        require_gte!(Collection::MAX_LOCK_TIERS, lock_tiers.len(), Errors::InvalidLockTier);
+
+        for tier in lock_tiers {
+            require_gt!(tier.duration, 0, Errors::InvalidLockTier);
+            require_gt!(tier.multiplier, 0, Errors::InvalidLockTier);
+        }
+
+        // Applies to the NFTs staked afterwards
+        self.lock_tiers = lock_tiers.to_vec();
+        Ok(())
+    }
+
+    /// Counts the staked NFTs and their multipliers in the shares of the emission pool
+    /// * the reward index must be updated first
+    pub fn add_stakes(&mut self, count: u64, weight: u64) -> Result<()> {
+        self.open_stakes = self.open_stakes.checked_add(count)
+            .ok_or(Errors::MathOverflow)?;
+        self.staked_weight = self.staked_weight.checked_add(weight)
+            .ok_or(Errors::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Removes the unstaked NFTs
+    /// * the reward index must be updated first
+    pub fn remove_stakes(&mut self, count: u64, weight: u64) -> Result<()> {
+        self.open_stakes = self.open_stakes.checked_sub(count)
+            .ok_or(Errors::MathOverflow)?;
+        self.staked_weight = self.staked_weight.checked_sub(weight)
+            .ok_or(Errors::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn set_emission_schedule(&mut self, emission_schedule: Option<EmissionSchedule>) -> Result<()> {
+        if let Some(schedule) = emission_schedule.as_ref() {
+            schedule.validate()?;
+        }
+
+        // The rewards accrued so far are kept at the previous schedule
+        self.update_reward_index()?;
+
+        self.emission_schedule = emission_schedule;
+        Ok(())
+    }
+
+    pub fn remove_stake(&mut self) -> Result<()> {
+        require_eq!(self.is_staking, true, Errors::ModuleNotActive);
+
+        self.is_staking = false;
+        Ok(())
+    }
+
+    pub fn apply_change(&mut self, change: &ConfigChange) -> Result<()> {
+        match change {
+            ConfigChange::Voting { new_num, edit_type } => {
+                self.edit_voting(*new_num, edit_type)
+            },
+            ConfigChange::Trade { new_num, edit_type } => {
+                self.edit_trade(*new_num, edit_type)
+            },
+            ConfigChange::Stake { emission } => {
+                self.edit_stake(*emission)
+            },
+            ConfigChange::RarityRoot { rarity_root } => {
+                // Applies to the NFTs staked afterwards
+                self.rarity_root = *rarity_root;
+                Ok(())
+            },
+            ConfigChange::LockTiers { lock_tiers } => {
+                self.set_lock_tiers(lock_tiers)
+            },
+            ConfigChange::EmissionSchedule { emission_schedule } => {
+                self.set_emission_schedule(*emission_schedule)
+            },
+            ConfigChange::CoReward { index, emission } => {
+                self.edit_co_reward(*index, *emission)
+            },
+            ConfigChange::UnbondingPeriod { unbonding_period } => {
+                require_gte!(*unbonding_period, 0, Errors::InvalidUnbondingPeriod);
+
+                // Applies to the NFTs unstaked afterwards
+                self.unbonding_period = *unbonding_period;
+                Ok(())
+            },
+            ConfigChange::Treasury { treasury } => {
+                require_keys_neq!(*treasury, Pubkey::default(), Errors::InvalidTreasury);
+                self.treasury_address = *treasury;
+                Ok(())
+            }
+        }
+    }
+
+    /// Fails if the collection is paused and the operation isn't in the allowlist
+    pub fn check_operation(&self, operation: PausableOperation) -> Result<()> {
+        if self.is_paused {
+            require_neq!(self.pause_allowlist & operation.flag(), 0, Errors::CollectionPaused);
+        }
+        Ok(())
+    }
+}
+
+/// A reward token emitted alongside the collection token
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,Copy)]
+pub struct CoReward {
+    /// The mint of the reward token (32)
+    pub mint: Pubkey,
+    /// The decimals of the reward token (1)
+    pub decimals: u8,
+    /// The vault paying the reward, the reward is minted by the token-authority PDA if not set (1 + 32)
+    pub reward_vault: Option<Pubkey>,
+    /// Receives the mint authority or the tokens left in the vault when the collection is closed (32)
+    pub authority: Pubkey,
+    /// The emission per hour of staking (with 9 decimals, scaled to the token decimals - 8)
+    pub emission: u64,
+    /// The rewards left to be paid (8)
+    pub emission_budget: u64,
+    /// The reward accrued per staked NFT (in token units, fixed point - 16)
+    pub reward_index: u128
+}
+
+impl CoReward {
+    pub const LEN: usize = 32 + 1 + 33 + 32 + 8 + 8 + 16;
+
+    pub fn new(
+        mint: Pubkey,
+        decimals: u8,
+        reward_vault: Option<Pubkey>,
+        authority: Pubkey,
+        emission: u64,
+        emission_budget: u64
+    ) -> Self {
+        Self {
+            mint,
+            decimals,
+            reward_vault,
+            authority,
+            emission,
+            emission_budget,
+            reward_index: 0
+        }
+    }
+
+    /// Adds the amount deposited into the vault to the emission budget
+    pub fn fund(&mut self, amount: u64) -> Result<()> {
+        require_gt!(amount, 0, Errors::ZeroValue);
+
+        if self.reward_vault.is_none() {
+            return Err(Errors::RewardVaultNotFound.into());
+        }
+
+        self.emission_budget = self.emission_budget.checked_add(amount)
+            .ok_or(Errors::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Deducts the reward from the emission budget and returns the amount which can be paid
+    /// * the amount is capped by the budget left and by the balance of the vault (if set)
+    /// * the claims keep the rest in the stake (see Stake::keep_unpaid)
+    pub fn claim_emission(&mut self, reward: u64, vault_balance: Option<u64>) -> u64 {
+        let amount = reward
+            .min(self.emission_budget)
+            .min(vault_balance.unwrap_or(u64::MAX));
+
+        self.emission_budget -= amount;
+        amount
+    }
+}
+
+/// How the emission of the staking module is split
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,Copy,PartialEq,Eq)]
+pub enum EmissionModel {
+    /// Each staked NFT earns the emission (the total emission grows with the staked NFTs)
+    PerNft,
+    /// The emission is a fixed pool shared by the staked NFTs, pro-rata to their multipliers
+    Pool
+}
+
+/// The schedule of the emission of the staking module
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,Copy)]
+pub struct EmissionSchedule {
+    /// The time from which the emission accrues (8)
+    pub start_time: i64,
+    /// The time after which nothing accrues (1 + 8)
+    pub end_time: Option<i64>,
+    /// The duration of each step of the emission (in seconds, 0 for no step-down - 8)
+    pub step_duration: i64,
+    /// The share of the emission kept at each step, in percent (e.g. 50 halves the emission - 2)
+    pub step_rate: u16
+}
+
+impl EmissionSchedule {
+    pub const LEN: usize = 8 + 1 + 8 + 8 + 2;
+
+    /// The fixed-point precision of the decay factors
+    const DECAY_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(end_time) = self.end_time {
+            require_gt!(end_time, self.start_time, Errors::InvalidEmissionSchedule);
+        }
+
+        require_gte!(self.step_duration, 0, Errors::InvalidEmissionSchedule);
+
+        // A step-down must lower the emission
+        if self.step_duration > 0 {
+            require_gt!(100, self.step_rate, Errors::InvalidEmissionSchedule);
+        }
+        Ok(())
+    }
+
+    /// Returns the reward accrued per NFT between the two times (see calc_reward_index)
+    /// * the emission of step k is the initial emission times (step_rate / 100)^k
+    /// * computed in closed form (the full steps in between are summed as a geometric series)
+    pub fn reward_index(&self, from: i64, to: i64, emission: u64, decimals: u8) -> Result<u128> {
+        let from = from.max(self.start_time);
+        let to = match self.end_time {
+            Some(end_time) => to.min(end_time),
+            None => to
+        };
+
+        if to <= from {
+            return Ok(0);
+        }
+
+        if self.step_duration == 0 {
+            return utils::calc_reward_index(to - from, emission, decimals);
+        }
+
+        let first_step = (from - self.start_time) / self.step_duration;
+        let last_step = (to - self.start_time) / self.step_duration;
+
+        if first_step == last_step {
+            let accrued = utils::calc_reward_index(to - from, emission, decimals)?;
+            return EmissionSchedule::scale(accrued, self.decay(first_step)?);
+        }
+
+        // The rest of the first step
+        let first_end = self.step_start(first_step + 1)?;
+        let first = utils::calc_reward_index(first_end - from, emission, decimals)?;
+        let mut accrued = EmissionSchedule::scale(first, self.decay(first_step)?)?;
+
+        // The full steps in between
+        if last_step > first_step + 1 {
+            let full_step = utils::calc_reward_index(self.step_duration, emission, decimals)?;
+            let steps_sum = self.decay_sum(first_step + 1, last_step)?;
+            accrued = accrued
+                .checked_add(EmissionSchedule::scale(full_step, steps_sum)?)
+                .ok_or(Errors::MathOverflow)?;
+        }
+
+        // The start of the last step
+        let last_start = self.step_start(last_step)?;
+        let last = utils::calc_reward_index(to - last_start, emission, decimals)?;
+
+        accrued
+            .checked_add(EmissionSchedule::scale(last, self.decay(last_step)?)?)
+            .ok_or(Errors::MathOverflow.into())
+    }
+
+    fn step_start(&self, step: i64) -> Result<i64> {
+        step
+            .checked_mul(self.step_duration)
+            .and_then(|offset| offset.checked_add(self.start_time))
+            .ok_or(Errors::MathOverflow.into())
+    }
+
+    /// The share of the emission left at the step, (step_rate / 100)^step in fixed point (by squaring)
+    fn decay(&self, step: i64) -> Result<u128> {
+        let precision = EmissionSchedule::DECAY_PRECISION;
+        let mut base = self.step_rate as u128 * precision / 100;
+        let mut exponent = step;
+        let mut result = precision;
+
+        while exponent > 0 && result > 0 {
+            if exponent & 1 == 1 {
+                result = result.checked_mul(base).ok_or(Errors::MathOverflow)? / precision;
+            }
+            base = base.checked_mul(base).ok_or(Errors::MathOverflow)? / precision;
+            exponent >>= 1;
+        }
+
+        Ok(result)
+    }
+
+    /// The sum of the decay factors of the steps from the first (inclusive) to the last (exclusive)
+    fn decay_sum(&self, first_step: i64, last_step: i64) -> Result<u128> {
+        let precision = EmissionSchedule::DECAY_PRECISION;
+        let ratio = self.step_rate as u128 * precision / 100;
+
+        // (q^first - q^last) / (1 - q), the step rate is below 100 when stepping
+        self.decay(first_step)?
+            .checked_sub(self.decay(last_step)?)
+            .and_then(|difference| difference.checked_mul(precision))
+            .and_then(|difference| difference.checked_div(precision - ratio))
+            .ok_or(Errors::MathOverflow.into())
+    }
+
+    /// Multiplies the reward index by the fixed-point factor
+    fn scale(reward_index: u128, factor: u128) -> Result<u128> {
+        let precision = EmissionSchedule::DECAY_PRECISION;
+
+        (reward_index / precision)
+            .checked_mul(factor)
+            .and_then(|whole| whole.checked_add(reward_index % precision * factor / precision))
+            .ok_or(Errors::MathOverflow.into())
+    }
+}
+
+/// A lock-up tier of the staking module
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,Copy)]
+pub struct LockTier {
+    /// The lock-up duration (in seconds - 8)
+    pub duration: i64,
+    /// The multiplier of the emission while locked in the tier, in percent (2)
+    pub multiplier: u16,
+    /// Whether the NFT can be unstaked before the unlock time, forfeiting the accrued rewards (1)
+    pub early_exit: bool
+}
+
+impl LockTier {
+    pub const LEN: usize = 8 + 2 + 1;
+}
+
+/// The bumps of the authority PDAs (seeded by the collection key)
+pub struct AuthorityBumps {
+    pub token_authority: u8,
+    pub nft_authority: u8,
+    pub escrow_authority: u8
+}
+
+impl AuthorityBumps {
+    pub fn find(collection: &Pubkey) -> Self {
+        let (_, token_authority) = Pubkey::find_program_address(
+            &[b"token-authority", collection.as_ref()],
+            &ID
+        );
+        let (_, nft_authority) = Pubkey::find_program_address(
+            &[b"nft-authority", collection.as_ref()],
+            &ID
+        );
+        let (_, escrow_authority) = Pubkey::find_program_address(
+            &[b"escrow", collection.as_ref()],
+            &ID
+        );
+
+        Self {
+            token_authority,
+            nft_authority,
+            escrow_authority
+        }
+    }
+}
+
+/// The original layout of the collection account (before the version field)
+#[derive(AnchorDeserialize)]
+pub struct CollectionV0 {
+    pub verified_collection_key: Pubkey,
+    pub treasury_address: Pubkey,
+    pub token_mint: Option<Pubkey>,
+    pub is_staking: bool,
+    pub is_voting: bool,
+    pub is_trade: bool,
+    pub is_community: bool,
+    pub count: u64,
+    pub vote_duration: i64,
+    pub quorum: u64,
+    pub trade_duration: i64,
+    pub trade_fees: u64,
+    pub emission: u64
+}
+
+impl CollectionV0 {
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 32 + 1 + 1 + 1 + 1 + 8 + 8 + 8 + 8 + 8 + 8;
+}
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,Copy,PartialEq,Eq)]
+pub enum PausableOperation {
+    StakeNft,
+    UnstakeNft,
+    WithdrawTokens,
+    CreateTrade,
+    AcceptTrade,
+    ExecuteTrade,
+    CancelTrade,
+    CreateProposal,
+    GiveVote,
+    ExecuteProposal,
+    CreateIdentity
+}
+
+impl PausableOperation {
+    pub fn flag(self) -> u16 {
+        1 << self as u16
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRECISION: u128 = EmissionSchedule::DECAY_PRECISION;
+    const EMISSION: u64 = 1_000_000_000_000;
+    const DAY: i64 = 86_400;
+
+    fn schedule(start_time: i64, end_time: Option<i64>, step_duration: i64, step_rate: u16) -> EmissionSchedule {
+        EmissionSchedule { start_time, end_time, step_duration, step_rate }
+    }
+
+    /// The reward index accrued step by step, the emission multiplied by the step rate at each step
+    fn stepwise_reward_index(schedule: &EmissionSchedule, from: i64, to: i64) -> u128 {
+        let from = from.max(schedule.start_time);
+        let to = schedule.end_time.map_or(to, |end_time| to.min(end_time));
+
+        if schedule.step_duration == 0 {
+            return if to > from { utils::calc_reward_index(to - from, EMISSION, 9).unwrap() } else { 0 };
+        }
+
+        let mut accrued = 0;
+        let mut factor = PRECISION;
+        let mut step_start = schedule.start_time;
+
+        while step_start < to && factor > 0 {
+            let step_end = step_start + schedule.step_duration;
+            let (start, end) = (from.max(step_start), to.min(step_end));
+
+            if end > start {
+                let reward_index = utils::calc_reward_index(end - start, EMISSION, 9).unwrap();
+                accrued += EmissionSchedule::scale(reward_index, factor).unwrap();
+            }
+
+            factor = factor * schedule.step_rate as u128 / 100;
+            step_start = step_end;
+        }
+
+        accrued
+    }
+
+    /// Compares the closed form with the step by step loop (up to the rounding of the decay factors)
+    fn assert_matches_stepwise(schedule: &EmissionSchedule, from: i64, to: i64) {
+        let closed_form = schedule.reward_index(from, to, EMISSION, 9).unwrap();
+        let stepwise = stepwise_reward_index(schedule, from, to);
+
+        let tolerance = stepwise / 1_000_000_000 + 1_000;
+        assert!(
+            closed_form.abs_diff(stepwise) <= tolerance,
+            "from {} to {}: closed form {} stepwise {}", from, to, closed_form, stepwise
+        );
+    }
+
+    #[test]
+    fn decay_is_the_power_of_the_step_rate() {
+        let halving = schedule(0, None, DAY, 50);
+        assert_eq!(halving.decay(0).unwrap(), PRECISION);
+        assert_eq!(halving.decay(1).unwrap(), PRECISION / 2);
+        assert_eq!(halving.decay(3).unwrap(), PRECISION / 8);
+
+        let no_rate = schedule(0, None, DAY, 0);
+        assert_eq!(no_rate.decay(0).unwrap(), PRECISION);
+        assert_eq!(no_rate.decay(1).unwrap(), 0);
+
+        // The factor of the slowest decay vanishes without overflowing
+        let slowest = schedule(0, None, DAY, 99);
+        assert_eq!(slowest.decay(10_000).unwrap(), 0);
+        assert_eq!(slowest.decay(i64::MAX).unwrap(), 0);
+    }
+
+    #[test]
+    fn decay_sum_matches_the_stepwise_sum() {
+        for step_rate in [0, 1, 50, 90, 99] {
+            let schedule = schedule(0, None, DAY, step_rate);
+
+            for (first_step, last_step) in [(0, 1), (0, 10), (3, 7), (1, 5_000)] {
+                let stepwise: u128 = (first_step..last_step).map(|step| schedule.decay(step).unwrap()).sum();
+                let closed_form = schedule.decay_sum(first_step, last_step).unwrap();
+
+                assert!(closed_form.abs_diff(stepwise) <= stepwise / 1_000_000_000 + 1_000);
+            }
+        }
+    }
+
+    #[test]
+    fn scale_multiplies_by_the_factor() {
+        assert_eq!(EmissionSchedule::scale(123_456_789, PRECISION).unwrap(), 123_456_789);
+        assert_eq!(EmissionSchedule::scale(u128::MAX / 2, PRECISION).unwrap(), u128::MAX / 2);
+        assert_eq!(EmissionSchedule::scale(1_000, PRECISION / 4).unwrap(), 250);
+        assert_eq!(EmissionSchedule::scale(1_000, 0).unwrap(), 0);
+        assert!(EmissionSchedule::scale(u128::MAX, 2 * PRECISION).is_err());
+    }
+
+    #[test]
+    fn reward_index_is_bounded_by_the_schedule() {
+        let schedule = schedule(1_000, Some(1_000 + 10 * DAY), DAY, 50);
+        let whole = schedule.reward_index(1_000, 1_000 + 10 * DAY, EMISSION, 9).unwrap();
+
+        assert_eq!(schedule.reward_index(0, 1_000, EMISSION, 9).unwrap(), 0);
+        assert_eq!(schedule.reward_index(1_000 + 10 * DAY, 1_000 + 20 * DAY, EMISSION, 9).unwrap(), 0);
+        assert_eq!(schedule.reward_index(500, 1_000 + 20 * DAY, EMISSION, 9).unwrap(), whole);
+        assert_eq!(schedule.reward_index(2_000, 1_500, EMISSION, 9).unwrap(), 0);
+    }
+
+    #[test]
+    fn reward_index_matches_the_stepwise_loop() {
+        for step_rate in [0, 50, 90, 99] {
+            let schedule = schedule(1_000, None, DAY, step_rate);
+
+            // Within a step, across two steps, across many steps and from the middle of the schedule
+            for (from, to) in [
+                (1_000, 1_000 + DAY / 2),
+                (1_000 + DAY / 2, 1_000 + 3 * DAY / 2),
+                (0, 1_000 + 30 * DAY + 17),
+                (1_000 + 5 * DAY + 3, 1_000 + 400 * DAY + 11)
+            ] {
+                assert_matches_stepwise(&schedule, from, to);
+            }
+        }
+
+        // Without a step-down the emission is flat
+        let flat = schedule(1_000, Some(1_000 + 30 * DAY), 0, 0);
+        assert_matches_stepwise(&flat, 0, 1_000 + 60 * DAY);
+    }
+
+    #[test]
+    fn reward_index_handles_a_huge_elapsed_time() {
+        // A century of daily steps, the emission has vanished long before the end
+        let schedule = schedule(0, None, DAY, 99);
+        let century = 36_525 * DAY;
+
+        assert_matches_stepwise(&schedule, 0, century);
+        assert_matches_stepwise(&schedule, 20_000 * DAY + 5, century);
+
+        let total = schedule.reward_index(0, century, EMISSION, 9).unwrap();
+        let longer = schedule.reward_index(0, 100 * century, EMISSION, 9).unwrap();
+        assert_eq!(total, longer);
+    }
+}
@@ -0,0 +1,179 @@
+use anchor_lang::prelude::*;
+use crate::Errors;
+use crate::states::{Collection,StakeMode,LockTier,EmissionModel,EmissionSchedule,PausableOperation};
+use crate::instructions::{TradeEditType,VotingEditType,TokenMetadataArgs};
+
+#[account]
+pub struct Council {
+    /// The public key of the associated collection (32)
+    pub collection: Pubkey,
+    /// The members of the council (4 + 32 * max 10)
+    pub members: Vec<Pubkey>,
+    /// The number of member approvals required to execute an action (1)
+    pub threshold: u8
+}
+
+impl Council {
+    pub const MAX_MEMBERS: usize = 10;
+    pub const LEN: usize = 8 + 32 + 4 + 32 * Council::MAX_MEMBERS + 1;
+
+    pub fn new(
+        collection: Pubkey,
+        members: Vec<Pubkey>,
+        threshold: u8
+    ) -> Result<Self> {
+        Council::validate(&members, threshold)?;
+
+        Ok(Self {
+            collection,
+            members,
+            threshold
+        })
+    }
+
+    /// Checks the member count, duplicates and the threshold (M of N)
+    pub fn validate(members: &[Pubkey], threshold: u8) -> Result<()> {
+        require_gt!(members.len(), 0, Errors::InvalidCouncil);
+        require_gte!(Council::MAX_MEMBERS, members.len(), Errors::InvalidCouncil);
+
+        for (i, member) in members.iter().enumerate() {
+            require_eq!(members[i + 1..].contains(member), false, Errors::InvalidCouncil);
+        }
+
+        require_gt!(threshold, 0, Errors::InvalidCouncil);
+        require_gte!(members.len(), threshold as usize, Errors::InvalidCouncil);
+
+        Ok(())
+    }
+
+    pub fn is_member(&self, key: &Pubkey) -> bool {
+        self.members.contains(key)
+    }
+}
+
+#[account]
+pub struct CouncilAction {
+    /// The public key of the council (32)
+    pub council: Pubkey,
+    /// The public key of the associated collection (32)
+    pub collection: Pubkey,
+    /// The change to be applied to the collection (max 326)
+    pub action: CouncilActionType,
+    /// The members who approved the action (4 + 32 * max 10)
+    pub approvals: Vec<Pubkey>,
+    /// The creator of the action, receives the rent back on execution (32)
+    pub creator: Pubkey
+}
+
+impl CouncilAction {
+    pub const LEN: usize = 8 + 32 + 32 + CouncilActionType::MAX_LEN +
+        4 + 32 * Council::MAX_MEMBERS + 32;
+
+    pub fn new(
+        council: Pubkey,
+        collection: Pubkey,
+        action: CouncilActionType,
+        creator: Pubkey
+    ) -> Self {
+        Self {
+            council,
+            collection,
+            action,
+            approvals: Vec::new(),
+            creator
+        }
+    }
+
+    /// Records the approval of a council member (who must sign the transaction)
+    pub fn approve(&mut self, council: &Council, member: &AccountInfo) -> Result<()> {
+        require_eq!(member.is_signer, true, Errors::NotCouncilMember);
+        require_eq!(council.is_member(member.key), true, Errors::NotCouncilMember);
+        require_eq!(self.approvals.contains(member.key), false, Errors::AlreadyApproved);
+
+        self.approvals.push(member.key());
+        Ok(())
+    }
+
+    /// The number of approvals given by the current members of the council
+    pub fn approval_count(&self, council: &Council) -> usize {
+        self.approvals
+            .iter()
+            .filter(|approver| council.is_member(approver))
+            .count()
+    }
+
+    /// Fails until the current members of the council reach the threshold of approvals
+    pub fn check_threshold(&self, council: &Council) -> Result<()> {
+        require_gte!(self.approval_count(council), council.threshold as usize, Errors::ThresholdNotReached);
+        Ok(())
+    }
+
+    /// Fails unless the approved action authorizing the instruction is passed (if the collection is managed by a council)
+    /// * the action is consumed by the instruction, which closes it to its creator
+    pub fn require_approved(
+        collection: &Collection,
+        council: Option<&Account<Council>>,
+        council_action: Option<&Account<CouncilAction>>,
+        action_creator: Option<&AccountInfo>,
+        is_authorized: impl FnOnce(&CouncilActionType) -> bool
+    ) -> Result<()> {
+        if collection.council.is_none() {
+            return Ok(());
+        }
+
+        let (council, council_action, action_creator) = match (council, council_action, action_creator) {
+            (Some(council), Some(council_action), Some(action_creator)) => {
+                (council, council_action, action_creator)
+            },
+            _ => {
+                return Err(Errors::CouncilRequired.into());
+            }
+        };
+
+        require_keys_eq!(council_action.creator, action_creator.key(), Errors::CouncilActionMismatch);
+        require!(is_authorized(&council_action.action), Errors::CouncilActionMismatch);
+
+        council_action.check_threshold(council)
+    }
+}
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone)]
+pub enum CouncilActionType {
+    AddVoting { count: u64, duration: i64, quorum: u64 },
+    EditVoting { new_num: u64, edit_type: VotingEditType },
+    RemoveVoting,
+    AddTrade { trade_fee: u64, duration: i64 },
+    EditTrade { new_num: u64, edit_type: TradeEditType },
+    RemoveTrade,
+    AddStake { emission: u64, max_supply: u64, stake_mode: StakeMode, emission_model: EmissionModel },
+    EditStake { emission: u64 },
+    RemoveStake,
+    TopUpEmission { amount: u64 },
+    SetRarityRoot { rarity_root: Option<[u8; 32]> },
+    SetLockTiers { lock_tiers: Vec<LockTier> },
+    SetEmissionSchedule { emission_schedule: Option<EmissionSchedule> },
+    SetUnbondingPeriod { unbonding_period: i64 },
+    EditCoReward { index: u8, emission: u64 },
+    UpdateTreasury { treasury: Pubkey },
+    WithdrawTreasury { destination: Pubkey, amount: u64 },
+    UpdateCouncil { members: Vec<Pubkey>, threshold: u8 },
+    /// Authorizes close_collection (consumed by the instruction instead of execute_council_action)
+    CloseCollection,
+    /// Authorizes cancel_pending_change for the pending change (consumed by the instruction)
+    CancelPendingChange { pending_change: Pubkey },
+    /// The actions below authorize the instruction of the same name (consumed by it, the admin still signs it)
+    SetPause { is_paused: bool, allowlist: Option<Vec<PausableOperation>> },
+    InitTreasuryVault,
+    ProposeAdmin { new_admin: Pubkey },
+    AddToken { token: Pubkey, metadata: Option<TokenMetadataArgs> },
+    AddRewardVault { token: Pubkey },
+    FundRewardVault { amount: u64 },
+    AddCoReward { token: Pubkey, emission: u64, emission_budget: u64 },
+    AddCoRewardVault { token: Pubkey, authority: Pubkey },
+    UpdateTokenMetadata { metadata: TokenMetadataArgs }
+}
+
+impl CouncilActionType {
+    /// The size of the largest variant (UpdateCouncil, the token metadata is bounded by the limits of the metadata program)
+    pub const MAX_LEN: usize = 1 + 4 + 32 * Council::MAX_MEMBERS + 1;
+}
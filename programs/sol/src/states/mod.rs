@@ -3,9 +3,15 @@ mod collection;
 mod proposal;
 mod stake;
 mod identity;
+mod council;
+mod pending_change;
+mod treasury;
 
 pub use trade::*;
 pub use collection::*;
 pub use proposal::*;
 pub use stake::*;
-pub use identity::*;
\ No newline at end of file
+pub use identity::*;
+pub use council::*;
+pub use pending_change::*;
+pub use treasury::*;
\ No newline at end of file
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use crate::instructions::{TradeEditType,VotingEditType};
+use crate::states::{Collection,LockTier,EmissionSchedule};
+
+#[account]
+pub struct PendingChange {
+    /// The public key of the associated collection (32)
+    pub collection: Pubkey,
+    /// The queued change of the collection configuration (max 49)
+    pub change: ConfigChange,
+    /// The earliest time at which the change can be applied (8)
+    pub apply_time: i64,
+    /// The payer of the account, receives the rent back on apply or cancel (32)
+    pub creator: Pubkey
+}
+
+impl PendingChange {
+    pub const LEN: usize = 8 + 32 + ConfigChange::MAX_LEN + 8 + 32;
+
+    pub fn new(
+        collection: Pubkey,
+        change: ConfigChange,
+        timelock: i64,
+        creator: Pubkey
+    ) -> Self {
+        let clock = Clock::get().unwrap();
+        let apply_time = clock.unix_timestamp + timelock;
+
+        Self {
+            collection,
+            change,
+            apply_time,
+            creator
+        }
+    }
+}
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone)]
+pub enum ConfigChange {
+    Voting { new_num: u64, edit_type: VotingEditType },
+    Trade { new_num: u64, edit_type: TradeEditType },
+    Stake { emission: u64 },
+    RarityRoot { rarity_root: Option<[u8; 32]> },
+    LockTiers { lock_tiers: Vec<LockTier> },
+    EmissionSchedule { emission_schedule: Option<EmissionSchedule> },
+    UnbondingPeriod { unbonding_period: i64 },
+    CoReward { index: u8, emission: u64 },
+    Treasury { treasury: Pubkey }
+}
+
+impl ConfigChange {
+    /// The size of the largest variant (LockTiers)
+    pub const MAX_LEN: usize = 1 + 4 + LockTier::LEN * Collection::MAX_LOCK_TIERS;
+}
@@ -3,6 +3,8 @@ use crate::Errors;
 
 #[account]
 pub struct Proposal {
+    /// The version of the account layout (1)
+    pub version: u8,
     /// The public key of the associated collection (32)
     pub collection: Pubkey,
     /// The text/title of the proposal (4 + max 300)
@@ -24,12 +26,14 @@ pub struct Proposal {
 }
 
 impl Proposal {
+    pub const VERSION: u8 = 1;
+
     pub fn len(
         nft_count: u64,
         title_size: usize,
         option_count: usize
     ) -> usize {
-        8 + 32 + 4 + title_size + 4 + (29 * option_count) +
+        8 + 1 + 32 + 4 + title_size + 4 + (29 * option_count) +
         4 + (8 * option_count) + 4 + 1 + nft_count as usize + 
         2 + 8 + 32
     }
@@ -45,6 +49,7 @@ impl Proposal {
         let clock = Clock::get().unwrap();
         let time = clock.unix_timestamp;
         Self { 
+            version: Proposal::VERSION,
             collection,
             proposal,
             votes,
@@ -57,6 +62,22 @@ impl Proposal {
         }
     }
 
+    /// Upgrades the original (unversioned) layout
+    pub fn from_v0(legacy: ProposalV0) -> Self {
+        Self {
+            version: Proposal::VERSION,
+            collection: legacy.collection,
+            proposal: legacy.proposal,
+            options: legacy.options,
+            votes: legacy.votes,
+            voters: legacy.voters,
+            is_active: legacy.is_active,
+            is_passed: legacy.is_passed,
+            time: legacy.time,
+            creator: legacy.creator
+        }
+    }
+
     pub fn increase_vote(&mut self, choice: u8) {
         self.votes[choice as usize] += 1;
     }
@@ -78,4 +99,29 @@ impl Proposal {
         Ok(())
     }
 
+}
+
+/// The original layout of the proposal account (before the version field)
+#[derive(AnchorDeserialize)]
+pub struct ProposalV0 {
+    pub collection: Pubkey,
+    pub proposal: String,
+    pub options: Vec<String>,
+    pub votes: Vec<u64>,
+    pub voters: Vec<u8>,
+    pub is_active: bool,
+    pub is_passed: bool,
+    pub time: i64,
+    pub creator: Pubkey
+}
+
+impl ProposalV0 {
+    /// The size allocated for the proposal by the original program
+    pub fn allocated_len(&self) -> usize {
+        Proposal::len(
+            self.voters.len() as u64 - 1,
+            self.proposal.len(),
+            self.options.len()
+        ) - 1
+    }
 }
\ No newline at end of file
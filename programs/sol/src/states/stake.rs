@@ -1,7 +1,11 @@
 use anchor_lang::prelude::*;
+use crate::{Errors, utils};
+use crate::states::{Collection,CoReward,LockTier};
 
 #[account]
 pub struct Stake {
+    /// The version of the account layout (1)
+    pub version: u8,
     /// The pubkey of the owner of the NFT (32)
     pub owner: Pubkey,
     /// The time of the staking (8)
@@ -9,25 +13,344 @@ pub struct Stake {
     /// The token account from which the NFT is sent (32)
     pub nft_send_address: Pubkey,
     /// The pubkey of the collection details account
-    pub collection: Pubkey
+    pub collection: Pubkey,
+    /// The reward index of the collection when the rewards were last claimed (16)
+    pub reward_index: u128,
+    /// The rewards accrued but not yet paid (credited on the migration, or kept when the payout ran short - 8)
+    pub unclaimed_reward: u64,
+    /// The reward indexes of the co-rewards of the collection when they were last claimed (16 * 2)
+    pub co_reward_indexes: [u128; Collection::MAX_CO_REWARDS],
+    /// The co-rewards accrued but not yet paid (kept when the payout ran short - 8 * 2)
+    pub unclaimed_co_rewards: [u64; Collection::MAX_CO_REWARDS],
+    /// Whether the NFT is held in the escrow or frozen in the token account of the owner (1)
+    pub stake_mode: StakeMode,
+    /// The multiplier of the emission (rarity and lock tier), in percent (2)
+    pub multiplier: u16,
+    /// The lock-up tier picked when staking (2)
+    pub lock_tier: Option<u8>,
+    /// The time until which the NFT can't be unstaked (8)
+    pub unlock_time: i64,
+    /// Whether the NFT can be unstaked before the unlock time, forfeiting the accrued rewards (1)
+    pub early_exit: bool,
+    /// The time from which the unbonding NFT can be returned (if unstaked with an unbonding period - 9)
+    pub unbonding_time: Option<i64>
 }
 
 impl Stake {
-    pub const LEN: usize = 8 + 32 + 8 + 32 + 32; 
+    pub const VERSION: u8 = 1;
+
+    pub const LEN: usize = 8 + 1 + 32 + 8 + 32 + 32 + 16 + 8 + 16 * Collection::MAX_CO_REWARDS + 8 * Collection::MAX_CO_REWARDS + 1 + 2 + 2 + 8 + 1 + 9; 
+
+    /// The most NFTs staked or unstaked in one batch (bounded by the compute budget)
+    pub const MAX_BATCH: usize = 8;
+
+    /// The most stakes claimed in one batch
+    pub const MAX_CLAIM_BATCH: usize = 20;
 
     pub fn new(
         owner: Pubkey,
         nft_send_address: Pubkey,
-        collection: Pubkey
+        collection: Pubkey,
+        reward_index: u128,
+        co_reward_indexes: [u128; Collection::MAX_CO_REWARDS],
+        stake_mode: StakeMode,
+        multiplier: u16
     ) -> Self {
         let clock = Clock::get().unwrap();
         let time = clock.unix_timestamp;
 
         Self { 
+            version: Stake::VERSION,
             owner, 
             time,
             nft_send_address,
-            collection
+            collection,
+            reward_index,
+            unclaimed_reward: 0,
+            co_reward_indexes,
+            unclaimed_co_rewards: [0; Collection::MAX_CO_REWARDS],
+            stake_mode,
+            multiplier,
+            lock_tier: None,
+            unlock_time: 0,
+            early_exit: false,
+            unbonding_time: None
+        }
+    }
+
+    /// Upgrades the original (unversioned) layout
+    /// * the rewards accrued under the original accounting are credited as unclaimed
+    pub fn from_v0(legacy: StakeV0, reward_index: u128, unclaimed_reward: u64) -> Self {
+        Self {
+            version: Stake::VERSION,
+            owner: legacy.owner,
+            time: legacy.time,
+            nft_send_address: legacy.nft_send_address,
+            collection: legacy.collection,
+            reward_index,
+            unclaimed_reward,
+            co_reward_indexes: [0; Collection::MAX_CO_REWARDS],
+            unclaimed_co_rewards: [0; Collection::MAX_CO_REWARDS],
+            stake_mode: StakeMode::Escrow,
+            multiplier: utils::BASE_MULTIPLIER,
+            lock_tier: None,
+            unlock_time: 0,
+            early_exit: false,
+            unbonding_time: None
+        }
+    }
+
+    /// Locks the stake in the tier for its lock-up duration, the tier boosts the multiplier
+    pub fn lock(&mut self, lock_tier: u8, tier: &LockTier) -> Result<()> {
+        let multiplier = (self.multiplier as u32)
+            .checked_mul(tier.multiplier as u32)
+            .ok_or(Errors::MathOverflow)? / utils::BASE_MULTIPLIER as u32;
+
+        self.multiplier = multiplier.try_into().map_err(|_| Errors::MathOverflow)?;
+        require_gt!(self.multiplier, 0, Errors::InvalidLockTier);
+
+        self.lock_tier = Some(lock_tier);
+        self.unlock_time = self.time
+            .checked_add(tier.duration)
+            .ok_or(Errors::MathOverflow)?;
+        self.early_exit = tier.early_exit;
+        Ok(())
+    }
+
+    pub fn is_locked(&self) -> Result<bool> {
+        Ok(Clock::get()?.unix_timestamp < self.unlock_time)
+    }
+
+    /// Fails while the stake is locked, unless its tier allows the early exit
+    /// * returns whether the accrued rewards are forfeited (the early exit)
+    pub fn check_unstake(&self) -> Result<bool> {
+        require!(self.unbonding_time.is_none(), Errors::StakeUnbonding);
+
+        if !self.is_locked()? {
+            return Ok(false);
+        }
+
+        require_eq!(self.early_exit, true, Errors::StakeLocked);
+        Ok(true)
+    }
+
+    /// Fails while the stake is locked in a tier with the early exit
+    /// (its rewards are paid once unlocked, else the early exit wouldn't forfeit them)
+    pub fn check_claim(&self) -> Result<()> {
+        // The accrual stopped when unbonding (the rewards were paid on unstake)
+        require!(self.unbonding_time.is_none(), Errors::StakeUnbonding);
+
+        if self.early_exit && self.is_locked()? {
+            return Err(Errors::StakeLocked.into());
+        }
+        Ok(())
+    }
+
+    /// Stops the accrual, the NFT is returned with complete_unstake once the unbonding period has passed
+    pub fn start_unbonding(&mut self, unbonding_period: i64) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+
+        self.unbonding_time = Some(
+            current_time
+                .checked_add(unbonding_period)
+                .ok_or(Errors::MathOverflow)?
+        );
+        Ok(())
+    }
+
+    /// Fails unless the stake is unbonding and its unbonding period has passed
+    pub fn check_complete_unstake(&self) -> Result<()> {
+        match self.unbonding_time {
+            Some(unbonding_time) => {
+                let current_time = Clock::get()?.unix_timestamp;
+                require_gte!(current_time, unbonding_time, Errors::StakeUnbonding);
+                Ok(())
+            },
+            None => {
+                Err(Errors::StakeNotUnbonding.into())
+            }
+        }
+    }
+
+    /// Returns the rewards accrued up to the reward index and checkpoints the stake at it
+    /// * the accrual is scaled by the rarity multiplier of the NFT
+    /// * the fraction of a token unit isn't paid but carried over to the next claim
+    pub fn claim_reward(&mut self, reward_index: u128) -> Result<u64> {
+        let (reward, consumed) = self.accrued_reward(self.reward_index, reward_index)?;
+
+        let reward = reward
+            .checked_add(self.unclaimed_reward)
+            .ok_or(Errors::MathOverflow)?;
+
+        self.reward_index += consumed;
+        self.unclaimed_reward = 0;
+        Ok(reward)
+    }
+
+    /// Returns the rewards of the co-rewards of the collection (in their order) and checkpoints the stake
+    pub fn claim_co_rewards(&mut self, co_rewards: &[CoReward]) -> Result<[u64; Collection::MAX_CO_REWARDS]> {
+        let mut rewards = [0; Collection::MAX_CO_REWARDS];
+
+        for (i, co_reward) in co_rewards.iter().enumerate() {
+            let (reward, consumed) = self.accrued_reward(self.co_reward_indexes[i], co_reward.reward_index)?;
+
+            rewards[i] = reward
+                .checked_add(self.unclaimed_co_rewards[i])
+                .ok_or(Errors::MathOverflow)?;
+            self.co_reward_indexes[i] += consumed;
+            self.unclaimed_co_rewards[i] = 0;
+        }
+        Ok(rewards)
+    }
+
+    /// Keeps the claimed rewards which couldn't be paid (the reward vault or the emission budget ran short),
+    /// they're paid by a later claim once topped up
+    pub fn keep_unpaid(&mut self, unpaid_reward: u64, unpaid_co_rewards: &[u64]) -> Result<()> {
+        self.unclaimed_reward = self.unclaimed_reward
+            .checked_add(unpaid_reward)
+            .ok_or(Errors::MathOverflow)?;
+
+        for (unclaimed, unpaid) in self.unclaimed_co_rewards.iter_mut().zip(unpaid_co_rewards) {
+            *unclaimed = unclaimed
+                .checked_add(*unpaid)
+                .ok_or(Errors::MathOverflow)?;
         }
+        Ok(())
     }
-}
\ No newline at end of file
+
+    /// Returns the rewards kept on the stake and clears them (the accrual has stopped, e.g. unbonding)
+    pub fn take_unclaimed(&mut self) -> (u64, [u64; Collection::MAX_CO_REWARDS]) {
+        let unclaimed = (self.unclaimed_reward, self.unclaimed_co_rewards);
+
+        self.unclaimed_reward = 0;
+        self.unclaimed_co_rewards = [0; Collection::MAX_CO_REWARDS];
+        unclaimed
+    }
+
+    /// Returns the whole token units accrued between the indexes (scaled by the multiplier)
+    /// and the index they consume
+    fn accrued_reward(&self, stake_index: u128, reward_index: u128) -> Result<(u64, u128)> {
+        let multiplier = self.multiplier as u128;
+        let base = utils::BASE_MULTIPLIER as u128;
+
+        let accrued = reward_index
+            .checked_sub(stake_index)
+            .ok_or(Errors::MathOverflow)?;
+        let scaled = accrued
+            .checked_mul(multiplier)
+            .ok_or(Errors::MathOverflow)? / base;
+
+        let reward = utils::calc_emission(scaled, 0)?;
+
+        // Only the index of the whole units paid is consumed (rounded up, so nothing is paid twice)
+        let paid = scaled - scaled % utils::REWARD_INDEX_PRECISION;
+        let consumed = (paid * base).div_ceil(multiplier);
+
+        Ok((reward, consumed))
+    }
+}
+
+/// The original layout of the stake account (before the version field)
+#[derive(AnchorDeserialize)]
+pub struct StakeV0 {
+    pub owner: Pubkey,
+    pub time: i64,
+    pub nft_send_address: Pubkey,
+    pub collection: Pubkey
+}
+
+impl StakeV0 {
+    pub const LEN: usize = 8 + 32 + 8 + 32 + 32;
+}
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,Copy,PartialEq,Eq)]
+pub enum StakeMode {
+    /// The NFT is moved to the nft-escrow account owned by the nft-authority PDA
+    Escrow,
+    /// The nft-authority PDA becomes the delegate and freezes the NFT in the wallet of the owner
+    Freeze,
+    /// The nft-authority PDA becomes the staking delegate and locks the pNFT in the wallet of the owner
+    /// * always used for the programmable NFTs (can't be selected for the collection)
+    Lock
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNIT: u128 = utils::REWARD_INDEX_PRECISION;
+
+    fn new_stake(multiplier: u16) -> Stake {
+        Stake {
+            version: Stake::VERSION,
+            owner: Pubkey::new_unique(),
+            time: 0,
+            nft_send_address: Pubkey::new_unique(),
+            collection: Pubkey::new_unique(),
+            reward_index: 0,
+            unclaimed_reward: 0,
+            co_reward_indexes: [0; Collection::MAX_CO_REWARDS],
+            unclaimed_co_rewards: [0; Collection::MAX_CO_REWARDS],
+            stake_mode: StakeMode::Escrow,
+            multiplier,
+            lock_tier: None,
+            unlock_time: 0,
+            early_exit: false,
+            unbonding_time: None
+        }
+    }
+
+    #[test]
+    fn accrued_reward_pays_the_whole_units() {
+        let stake = new_stake(utils::BASE_MULTIPLIER);
+
+        assert_eq!(stake.accrued_reward(0, 0).unwrap(), (0, 0));
+        assert_eq!(stake.accrued_reward(0, UNIT - 1).unwrap(), (0, 0));
+        assert_eq!(stake.accrued_reward(0, 5 * UNIT + UNIT / 2).unwrap(), (5, 5 * UNIT));
+        assert_eq!(stake.accrued_reward(2 * UNIT, 5 * UNIT).unwrap(), (3, 3 * UNIT));
+    }
+
+    #[test]
+    fn accrued_reward_is_scaled_by_the_multiplier() {
+        // 3 units at 150% are 4.5 units, the 4 paid consume 8/3 units of the index (rounded up)
+        let stake = new_stake(150);
+        assert_eq!(stake.accrued_reward(0, 3 * UNIT).unwrap(), (4, (4 * UNIT * 100).div_ceil(150)));
+
+        let stake = new_stake(50);
+        assert_eq!(stake.accrued_reward(0, 3 * UNIT).unwrap(), (1, 2 * UNIT));
+    }
+
+    #[test]
+    fn accrued_reward_rejects_an_index_behind_the_stake() {
+        let stake = new_stake(utils::BASE_MULTIPLIER);
+        assert!(stake.accrued_reward(5 * UNIT, 4 * UNIT).is_err());
+    }
+
+    #[test]
+    fn claims_carry_the_fractions_over() {
+        for multiplier in [50, 100, 137, 300] {
+            let mut stake = new_stake(multiplier);
+            let mut reward_index = 0;
+            let mut paid = 0;
+
+            for _ in 0..1_000 {
+                reward_index += UNIT / 3 + 7;
+                paid += stake.claim_reward(reward_index).unwrap() as u128;
+            }
+
+            // Nothing is paid twice and at most the last fraction is left over
+            let owed = reward_index * multiplier as u128 / utils::BASE_MULTIPLIER as u128 / UNIT;
+            assert!(paid <= owed && owed - paid <= 1, "multiplier {}: paid {} owed {}", multiplier, paid, owed);
+        }
+    }
+
+    #[test]
+    fn claim_reward_pays_the_kept_rewards() {
+        let mut stake = new_stake(utils::BASE_MULTIPLIER);
+        stake.keep_unpaid(7, &[1, 2]).unwrap();
+
+        assert_eq!(stake.claim_reward(2 * UNIT).unwrap(), 9);
+        assert_eq!(stake.unclaimed_reward, 0);
+        assert_eq!(stake.take_unclaimed(), (0, [1, 2]));
+        assert_eq!(stake.unclaimed_co_rewards, [0, 0]);
+    }
+}
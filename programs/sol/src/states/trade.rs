@@ -2,13 +2,15 @@ use anchor_lang::prelude::*;
 
 #[account]
 pub struct Trade {
+    /// The version of the account layout
+    pub version: u8,
     /// The public key of the first party (initiator) of the trade
     pub party_one: Pubkey,
     /// The public key of the second party of the trade
     pub party_two: Pubkey,
     /// SOL amount to be exchanged in the transaction
     pub sol_amount: [u64;2],
-    /// SPL amount to be exchanged in the transaction
+    /// SPL amount to be exchanged in the transaction (the amount received by the escrow)
     pub spl_amount: [u64;2],
     /// SPL send account of the first party (outgoing)
     pub one_send_address: Option<Pubkey>,
@@ -25,38 +27,91 @@ pub struct Trade {
     /// Whether the trade is confirmed by the second party
     pub is_confirmed: bool,
     /// The collection key (for trade time and trade fee)
-    pub collection: Pubkey
+    pub collection: Pubkey,
+    /// The trade fee (in lamports) charged on execution, fixed when the trade is accepted
+    pub trade_fee: u64
 
 }
 
 impl Trade {
-    pub const LEN: usize = 8 + 32 + 32 + 16 + 16 + 1 + 32 + 1 + 32 + 1 + 32 + 8 + 1 + 32 + 2 + 64;
+    pub const VERSION: u8 = 1;
+
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 16 + 16 + 1 + 32 + 1 + 32 + 1 + 32 + 8 + 1 + 32 + 8 + 2 + 64;
 
     pub fn new(
         party_one: Pubkey,
         party_two: Pubkey,
-        sol_amount: u64,
-        spl_amount: u64,
-        one_send_address: Option<Pubkey>,
+        offer: TradeOffer,
         time: i64,
-        collection: Pubkey,
-        one_mint: Option<Pubkey>
+        collection: Pubkey
     ) -> Self {
         Self { 
+            version: Trade::VERSION,
             party_one, 
             party_two, 
-            sol_amount: [sol_amount,0], 
-            spl_amount: [spl_amount,0], 
-            one_send_address, 
+            sol_amount: [offer.sol_amount,0], 
+            spl_amount: [offer.spl_amount,0], 
+            one_send_address: offer.send_address, 
             two_send_address: None,
             two_receive_address: None, 
             time, 
             is_confirmed: false,
             collection,
-            one_mint,
-            two_mint: None
+            one_mint: offer.mint,
+            two_mint: None,
+            trade_fee: 0
         }
     }
+
+    /// Upgrades the original (unversioned) layout
+    pub fn from_v0(legacy: TradeV0) -> Self {
+        Self {
+            version: Trade::VERSION,
+            party_one: legacy.party_one,
+            party_two: legacy.party_two,
+            sol_amount: legacy.sol_amount,
+            spl_amount: legacy.spl_amount,
+            one_send_address: legacy.one_send_address,
+            one_mint: legacy.one_mint,
+            two_send_address: legacy.two_send_address,
+            two_receive_address: legacy.two_receive_address,
+            two_mint: legacy.two_mint,
+            time: legacy.time,
+            is_confirmed: legacy.is_confirmed,
+            collection: legacy.collection,
+            trade_fee: 0
+        }
+    }
+}
+
+/// What the first party puts into the trade when creating it
+pub struct TradeOffer {
+    pub sol_amount: u64,
+    /// The amount received by the escrow
+    pub spl_amount: u64,
+    pub send_address: Option<Pubkey>,
+    pub mint: Option<Pubkey>
+}
+
+/// The original layout of the trade account (before the version field)
+#[derive(AnchorDeserialize)]
+pub struct TradeV0 {
+    pub party_one: Pubkey,
+    pub party_two: Pubkey,
+    pub sol_amount: [u64;2],
+    pub spl_amount: [u64;2],
+    pub one_send_address: Option<Pubkey>,
+    pub one_mint: Option<Pubkey>,
+    pub two_send_address: Option<Pubkey>,
+    pub two_receive_address: Option<Pubkey>,
+    pub two_mint: Option<Pubkey>,
+    pub time: i64,
+    pub is_confirmed: bool,
+    pub collection: Pubkey
+}
+
+impl TradeV0 {
+    pub const LEN: usize = 8 + 32 + 32 + 16 + 16 + 1 + 32 + 1 + 32 + 1 + 32 + 8 + 1 + 32 + 2 + 64;
 }
 
 #[derive(AnchorDeserialize,AnchorSerialize)]
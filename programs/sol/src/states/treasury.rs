@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use crate::Errors;
+
+#[account]
+pub struct TreasuryVault {
+    /// The public key of the associated collection (32)
+    pub collection: Pubkey
+}
+
+impl TreasuryVault {
+    pub const LEN: usize = 8 + 32;
+
+    pub fn new(collection: Pubkey) -> Self {
+        Self {
+            collection
+        }
+    }
+
+    /// Moves lamports out of the vault, which must stay rent exempt
+    pub fn withdraw(vault: &AccountInfo, destination: &AccountInfo, amount: u64) -> Result<()> {
+        let rent_exempt = Rent::get()?.minimum_balance(TreasuryVault::LEN);
+        let available = vault.lamports().saturating_sub(rent_exempt);
+
+        require_gte!(available, amount, Errors::InsufficientBalance);
+
+        **vault.try_borrow_mut_lamports()? -= amount;
+        **destination.try_borrow_mut_lamports()? += amount;
+
+        Ok(())
+    }
+}
@@ -1,12 +1,38 @@
 use anchor_lang::prelude::*;
+use crate::Errors;
 
-pub fn calc_emission(stake_time: i64, emission: u64) -> u64 {
-    let clock = Clock::get().unwrap();
-    let current_time = clock.unix_timestamp;
+/// The number of decimals in which the emission is expressed
+pub const EMISSION_DECIMALS: u8 = 9;
 
-    let reward_time = (current_time - stake_time) as u64;
+/// The fixed-point precision of the reward index
+pub const REWARD_INDEX_PRECISION: u128 = 1_000_000_000_000;
 
-    let reward = reward_time * emission;
+/// Returns the reward accrued per NFT over the elapsed time (in token units, fixed point)
+pub fn calc_reward_index(elapsed: i64, emission: u64, decimals: u8) -> Result<u128> {
+    let elapsed: u128 = elapsed.try_into().map_err(|_| Errors::MathOverflow)?;
 
-    reward / 3600
-}
\ No newline at end of file
+    let reward = elapsed
+        .checked_mul(emission as u128)
+        .and_then(|reward| reward.checked_mul(REWARD_INDEX_PRECISION))
+        .ok_or(Errors::MathOverflow)?;
+
+    // Scales the reward from the emission decimals to the decimals of the token
+    let reward = if decimals >= EMISSION_DECIMALS {
+        10u128.checked_pow((decimals - EMISSION_DECIMALS) as u32)
+            .and_then(|scale| reward.checked_mul(scale))
+            .ok_or(Errors::MathOverflow)?
+    } else {
+        reward / 10u128.pow((EMISSION_DECIMALS - decimals) as u32)
+    };
+
+    Ok(reward / 3600)
+}
+
+/// Returns the reward (in token units) accrued between the two reward indexes
+pub fn calc_emission(reward_index: u128, stake_index: u128) -> Result<u64> {
+    let reward = reward_index
+        .checked_sub(stake_index)
+        .ok_or(Errors::MathOverflow)? / REWARD_INDEX_PRECISION;
+
+    Ok(reward.try_into().map_err(|_| Errors::MathOverflow)?)
+}
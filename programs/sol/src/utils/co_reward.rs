@@ -0,0 +1,154 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token,
+    token_2022,
+    token_interface::{
+        self,
+        TokenAccount,
+        MintTo,
+        TransferChecked
+    }
+};
+use crate::states::{Collection,CoReward};
+use crate::Errors;
+
+/// The accounts of each co-reward at the end of the remaining accounts (in the order of the co-rewards):
+/// mint, token program, token account of the receiver and the vault (if the co-reward is paid from a vault)
+pub fn co_reward_accounts_len(co_rewards: &[CoReward]) -> usize {
+    co_rewards
+        .iter()
+        .map(|co_reward| if co_reward.reward_vault.is_some() { 4 } else { 3 })
+        .sum()
+}
+
+/// Splits the remaining accounts into the accounts of the instruction and the accounts of the co-rewards
+pub fn split_co_reward_accounts<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+    co_rewards: &[CoReward]
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+    let len = remaining_accounts.len()
+        .checked_sub(co_reward_accounts_len(co_rewards))
+        .ok_or(Errors::AccountNotProvided)?;
+
+    Ok(remaining_accounts.split_at(len))
+}
+
+/// The accounts of a co-reward, validated against its configuration
+pub struct CoRewardAccounts<'info> {
+    pub mint: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    pub reward_vault: Option<InterfaceAccount<'info, TokenAccount>>
+}
+
+impl<'info> CoRewardAccounts<'info> {
+    /// Reads the next accounts of the co-reward, the token account must be held by the receiver
+    pub fn next<'a>(
+        accounts: &mut std::slice::Iter<'a, AccountInfo<'info>>,
+        co_reward: &CoReward,
+        receiver: &Pubkey
+    ) -> Result<Self> {
+        let mint = next_account_info(accounts)?;
+        require_keys_eq!(mint.key(), co_reward.mint, Errors::InvalidCoReward);
+
+        let token_program = next_account_info(accounts)?;
+        require!(
+            token_program.key() == token::ID || token_program.key() == token_2022::ID,
+            ErrorCode::InvalidProgramId
+        );
+
+        let token_account: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(next_account_info(accounts)?)?;
+        require_keys_eq!(token_account.mint, co_reward.mint, Errors::IncorrectTokenAccount);
+        require_keys_eq!(token_account.owner, *receiver, Errors::IncorrectTokenAccount);
+
+        let reward_vault = match co_reward.reward_vault {
+            Some(reward_vault) => {
+                let vault_info = next_account_info(accounts)?;
+                require_keys_eq!(vault_info.key(), reward_vault, Errors::RewardVaultNotFound);
+
+                Some(InterfaceAccount::try_from(vault_info)?)
+            },
+            None => None
+        };
+
+        Ok(Self {
+            mint: mint.clone(),
+            token_program: token_program.clone(),
+            token_account,
+            reward_vault
+        })
+    }
+
+    /// Transfers the reward from the vault, or mints it if the co-reward has no vault
+    pub fn pay(
+        &self,
+        amount: u64,
+        decimals: u8,
+        token_authority: &AccountInfo<'info>,
+        token_seed: &[&[u8]]
+    ) -> Result<()> {
+        match self.reward_vault.as_ref() {
+            Some(reward_vault) => {
+                let cpi_accounts = TransferChecked {
+                    from: reward_vault.to_account_info(),
+                    mint: self.mint.clone(),
+                    to: self.token_account.to_account_info(),
+                    authority: token_authority.clone()
+                };
+                let cpi_context = CpiContext::new(self.token_program.clone(), cpi_accounts);
+
+                token_interface::transfer_checked(cpi_context.with_signer(&[token_seed]), amount, decimals)
+            },
+            None => {
+                let cpi_accounts = MintTo {
+                    mint: self.mint.clone(),
+                    to: self.token_account.to_account_info(),
+                    authority: token_authority.clone()
+                };
+                let cpi_context = CpiContext::new(self.token_program.clone(), cpi_accounts);
+
+                token_interface::mint_to(cpi_context.with_signer(&[token_seed]), amount)
+            }
+        }
+    }
+}
+
+/// Pays the rewards of the co-rewards (in their order), capped by their budgets and vaults
+/// * returns the amounts paid (the claims keep the rest in the stake)
+pub fn pay_co_rewards<'info>(
+    co_rewards: &mut [CoReward],
+    rewards: &[u64],
+    accounts: &[AccountInfo<'info>],
+    receiver: &Pubkey,
+    token_authority: &AccountInfo<'info>,
+    token_seed: &[&[u8]]
+) -> Result<[u64; Collection::MAX_CO_REWARDS]> {
+    let accounts = &mut accounts.iter();
+    let mut payouts = [0; Collection::MAX_CO_REWARDS];
+
+    for ((co_reward, reward), paid) in co_rewards.iter_mut().zip(rewards).zip(payouts.iter_mut()) {
+        let co_reward_accounts = CoRewardAccounts::next(accounts, co_reward, receiver)?;
+
+        let vault_balance = co_reward_accounts.reward_vault.as_ref().map(|reward_vault| reward_vault.amount);
+        let payout = co_reward.claim_emission(*reward, vault_balance);
+
+        if payout > 0 {
+            co_reward_accounts.pay(payout, co_reward.decimals, token_authority, token_seed)?;
+        }
+        *paid = payout;
+    }
+    Ok(payouts)
+}
+
+/// Returns the part of the rewards which wasn't paid
+pub fn unpaid_rewards(
+    rewards: &[u64; Collection::MAX_CO_REWARDS],
+    payouts: &[u64; Collection::MAX_CO_REWARDS]
+) -> [u64; Collection::MAX_CO_REWARDS] {
+    let mut unpaid = [0; Collection::MAX_CO_REWARDS];
+
+    for ((unpaid, reward), payout) in unpaid.iter_mut().zip(rewards).zip(payouts) {
+        *unpaid = reward - payout;
+    }
+    unpaid
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+/// Creates the PDA account (the payer covers the rent), used where the account
+/// can't be initialized by the constraints (e.g. the remaining accounts of a batch)
+/// * an address funded beforehand is topped up to the rent, allocated and assigned instead (as init does)
+pub fn create_pda_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    signer_seeds: &[&[u8]]
+) -> Result<()> {
+    let rent_exempt = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+
+    if current_lamports == 0 {
+        return system_program::create_account(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone()
+                }
+            ).with_signer(&[signer_seeds]),
+            rent_exempt,
+            space as u64,
+            owner
+        );
+    }
+
+    let top_up = rent_exempt.saturating_sub(current_lamports);
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone()
+                }
+            ),
+            top_up
+        )?;
+    }
+
+    system_program::allocate(
+        CpiContext::new(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: account.clone()
+            }
+        ).with_signer(&[signer_seeds]),
+        space as u64
+    )?;
+
+    system_program::assign(
+        CpiContext::new(
+            system_program.clone(),
+            system_program::Assign {
+                account_to_assign: account.clone()
+            }
+        ).with_signer(&[signer_seeds]),
+        owner
+    )
+}
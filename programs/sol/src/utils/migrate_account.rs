@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{system_program, Discriminator};
+
+use crate::Errors;
+
+/// Verifies the discriminator of the account and returns its data (without the discriminator)
+pub fn read_account_data<T: Discriminator>(account: &AccountInfo) -> Result<Vec<u8>> {
+    let data = account.try_borrow_data()?;
+
+    require_gte!(data.len(), 8, Errors::AccountNotInitialized);
+    require!(data[..8] == T::discriminator(), ErrorCode::AccountDiscriminatorMismatch);
+
+    Ok(data[8..].to_vec())
+}
+
+/// Resizes the account to the new layout (the payer covers the extra rent)
+/// and writes the upgraded account into it
+pub fn write_migrated_account<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+    upgraded: &T
+) -> Result<()> {
+    // Only the rent of the extra space is charged (the account may hold other lamports, e.g. a trade)
+    let rent = Rent::get()?;
+    let lamports_needed = rent.minimum_balance(new_len)
+        .saturating_sub(rent.minimum_balance(account.data_len()));
+
+    if lamports_needed > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone()
+                }
+            ),
+            lamports_needed
+        )?;
+    }
+
+    account.realloc(new_len, true)?;
+
+    let mut data = account.try_borrow_mut_data()?;
+    upgraded.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+/// Closes an account left in an original layout (it can't be loaded to be closed by Anchor)
+pub fn close_legacy_account<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    **destination.try_borrow_mut_lamports()? = destination.lamports()
+        .checked_add(lamports)
+        .ok_or(Errors::MathOverflow)?;
+
+    account.assign(&system_program::ID);
+    account.realloc(0, false).map_err(Into::into)
+}
@@ -1,7 +1,23 @@
 mod validate_metadata;
 mod validate_token;
 mod calc_emission;
+mod migrate_account;
+mod programmable_nft;
+mod token_transfer;
+mod init_account;
+mod verify_rarity;
+mod co_reward;
+mod pay_reward;
+mod unique_accounts;
 
 pub use validate_metadata::*;
 pub use validate_token::*;
-pub use calc_emission::*;
\ No newline at end of file
+pub use calc_emission::*;
+pub use migrate_account::*;
+pub use programmable_nft::*;
+pub use token_transfer::*;
+pub use init_account::*;
+pub use verify_rarity::*;
+pub use co_reward::*;
+pub use pay_reward::*;
+pub use unique_accounts::*;
\ No newline at end of file
@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    self,
+    TokenAccount,
+    Mint,
+    MintTo,
+    TransferChecked
+};
+use crate::Errors;
+
+/// Transfers the reward from the reward vault of the collection, or mints it if the collection has no vault
+pub fn pay_reward<'info>(
+    amount: u64,
+    token_mint: &InterfaceAccount<'info, Mint>,
+    token_receive_address: &InterfaceAccount<'info, TokenAccount>,
+    reward_vault: Option<&InterfaceAccount<'info, TokenAccount>>,
+    token_authority: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    token_seed: &[&[u8]]
+) -> Result<()> {
+    match reward_vault {
+        Some(reward_vault) => {
+            let cpi_accounts = TransferChecked {
+                from: reward_vault.to_account_info(),
+                mint: token_mint.to_account_info(),
+                to: token_receive_address.to_account_info(),
+                authority: token_authority.clone()
+            };
+            let cpi_context = CpiContext::new(token_program.clone(), cpi_accounts);
+
+            token_interface::transfer_checked(cpi_context.with_signer(&[token_seed]), amount, token_mint.decimals)
+        },
+        None => {
+            let cpi_accounts = MintTo {
+                mint: token_mint.to_account_info(),
+                to: token_receive_address.to_account_info(),
+                authority: token_authority.clone()
+            };
+            let cpi_context = CpiContext::new(token_program.clone(), cpi_accounts);
+
+            token_interface::mint_to(cpi_context.with_signer(&[token_seed]), amount)
+        }
+    }
+}
+
+/// Fails if a part of the rewards couldn't be paid (the reward vault or the emission budget ran short),
+/// unless the holder opted to forfeit it along with the stake
+pub fn require_paid(unpaid_reward: u64, unpaid_co_rewards: &[u64], forfeit_unpaid: bool) -> Result<()> {
+    let is_unpaid = unpaid_reward > 0 || unpaid_co_rewards.iter().any(|unpaid| *unpaid > 0);
+
+    require!(!is_unpaid || forfeit_unpaid, Errors::RewardsUnpaid);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_a_full_payout() {
+        assert!(require_paid(0, &[0, 0], false).is_ok());
+    }
+
+    #[test]
+    fn rejects_an_unpaid_rest_unless_forfeited() {
+        assert!(require_paid(5, &[0, 0], false).is_err());
+        assert!(require_paid(0, &[0, 3], false).is_err());
+        assert!(require_paid(5, &[0, 3], true).is_ok());
+    }
+}
@@ -0,0 +1,399 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::Instruction,
+    program::invoke_signed,
+    sysvar
+};
+use anchor_spl::{associated_token, metadata::MetadataAccount};
+use mpl_token_metadata::instruction::{
+    builders::{DelegateBuilder, RevokeBuilder, LockBuilder, UnlockBuilder, TransferBuilder},
+    DelegateArgs,
+    RevokeArgs,
+    LockArgs,
+    UnlockArgs,
+    TransferArgs,
+    InstructionBuilder
+};
+use mpl_token_metadata::state::TokenStandard;
+
+use crate::{Errors, MPL_TOKEN_METADATA_ID, MPL_TOKEN_AUTH_RULES_ID};
+
+/// The accounts of a programmable NFT held in the token account of its owner
+pub struct ProgrammableNft<'a, 'info> {
+    pub metadata: &'a AccountInfo<'info>,
+    pub edition: &'a AccountInfo<'info>,
+    pub token_record: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub token: &'a AccountInfo<'info>,
+    pub owner: &'a AccountInfo<'info>,
+    /// The staking delegate (the nft-authority PDA)
+    pub delegate: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub sysvar_instructions: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub metadata_program: &'a AccountInfo<'info>,
+    pub authorization_rules_program: Option<&'a AccountInfo<'info>>,
+    pub authorization_rules: Option<&'a AccountInfo<'info>>
+}
+
+impl<'a, 'info> ProgrammableNft<'a, 'info> {
+    /// Approves the delegate as the staking delegate of the NFT (signed by the owner)
+    pub fn delegate(&self) -> Result<()> {
+        let mut builder = DelegateBuilder::new();
+        builder
+            .delegate(self.delegate.key())
+            .metadata(self.metadata.key())
+            .master_edition(self.edition.key())
+            .token_record(self.token_record.key())
+            .mint(self.mint.key())
+            .token(self.token.key())
+            .authority(self.owner.key())
+            .payer(self.owner.key())
+            .system_program(self.system_program.key())
+            .sysvar_instructions(self.sysvar_instructions.key())
+            .spl_token_program(self.token_program.key());
+        self.set_rules(&mut *builder, DelegateBuilder::authorization_rules_program, DelegateBuilder::authorization_rules);
+
+        let instruction = builder
+            .build(DelegateArgs::StakingV1 { amount: 1, authorization_data: None })
+            .map_err(|_| Errors::AccountNotProvided)?
+            .instruction();
+
+        self.invoke(&instruction, &[])
+    }
+
+    /// Revokes the staking delegate of the NFT (signed by the owner)
+    pub fn revoke(&self) -> Result<()> {
+        let mut builder = RevokeBuilder::new();
+        builder
+            .delegate(self.delegate.key())
+            .metadata(self.metadata.key())
+            .master_edition(self.edition.key())
+            .token_record(self.token_record.key())
+            .mint(self.mint.key())
+            .token(self.token.key())
+            .authority(self.owner.key())
+            .payer(self.owner.key())
+            .system_program(self.system_program.key())
+            .sysvar_instructions(self.sysvar_instructions.key())
+            .spl_token_program(self.token_program.key());
+        self.set_rules(&mut *builder, RevokeBuilder::authorization_rules_program, RevokeBuilder::authorization_rules);
+
+        let instruction = builder
+            .build(RevokeArgs::StakingV1)
+            .map_err(|_| Errors::AccountNotProvided)?
+            .instruction();
+
+        self.invoke(&instruction, &[])
+    }
+
+    /// Locks the NFT in the token account of the owner (signed by the delegate)
+    pub fn lock(&self, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let mut builder = LockBuilder::new();
+        builder
+            .authority(self.delegate.key())
+            .token_owner(self.owner.key())
+            .token(self.token.key())
+            .mint(self.mint.key())
+            .metadata(self.metadata.key())
+            .edition(self.edition.key())
+            .token_record(self.token_record.key())
+            .payer(self.owner.key())
+            .system_program(self.system_program.key())
+            .sysvar_instructions(self.sysvar_instructions.key())
+            .spl_token_program(self.token_program.key());
+        self.set_rules(&mut *builder, LockBuilder::authorization_rules_program, LockBuilder::authorization_rules);
+
+        let instruction = builder
+            .build(LockArgs::V1 { authorization_data: None })
+            .map_err(|_| Errors::AccountNotProvided)?
+            .instruction();
+
+        self.invoke(&instruction, signer_seeds)
+    }
+
+    /// Unlocks the NFT in the token account of the owner (signed by the delegate)
+    pub fn unlock(&self, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let mut builder = UnlockBuilder::new();
+        builder
+            .authority(self.delegate.key())
+            .token_owner(self.owner.key())
+            .token(self.token.key())
+            .mint(self.mint.key())
+            .metadata(self.metadata.key())
+            .edition(self.edition.key())
+            .token_record(self.token_record.key())
+            .payer(self.owner.key())
+            .system_program(self.system_program.key())
+            .sysvar_instructions(self.sysvar_instructions.key())
+            .spl_token_program(self.token_program.key());
+        self.set_rules(&mut *builder, UnlockBuilder::authorization_rules_program, UnlockBuilder::authorization_rules);
+
+        let instruction = builder
+            .build(UnlockArgs::V1 { authorization_data: None })
+            .map_err(|_| Errors::AccountNotProvided)?
+            .instruction();
+
+        self.invoke(&instruction, signer_seeds)
+    }
+
+    /// Sets the rule set of the NFT on the builder, if it has one
+    fn set_rules<B>(
+        &self,
+        builder: &mut B,
+        set_rules_program: fn(&mut B, Pubkey) -> &mut B,
+        set_rules: fn(&mut B, Pubkey) -> &mut B
+    ) {
+        if let Some(authorization_rules_program) = self.authorization_rules_program {
+            set_rules_program(builder, authorization_rules_program.key());
+        }
+
+        if let Some(authorization_rules) = self.authorization_rules {
+            set_rules(builder, authorization_rules.key());
+        }
+    }
+
+    fn invoke(&self, instruction: &Instruction, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let mut account_infos = vec![
+            self.metadata.clone(),
+            self.edition.clone(),
+            self.token_record.clone(),
+            self.mint.clone(),
+            self.token.clone(),
+            self.owner.clone(),
+            self.delegate.clone(),
+            self.system_program.clone(),
+            self.sysvar_instructions.clone(),
+            self.token_program.clone(),
+            self.metadata_program.clone()
+        ];
+
+        if let Some(authorization_rules_program) = self.authorization_rules_program {
+            account_infos.push(authorization_rules_program.clone());
+        }
+
+        if let Some(authorization_rules) = self.authorization_rules {
+            account_infos.push(authorization_rules.clone());
+        }
+
+        invoke_signed(instruction, &account_infos, signer_seeds).map_err(Into::into)
+    }
+}
+
+/// The accounts of the metadata program to move a programmable NFT in or out of a trade escrow,
+/// at the front of the remaining accounts (for each programmable leg, in the order of the legs):
+/// metadata, edition, token record of the sender, token record of the receiver, instructions sysvar,
+/// metadata program, associated token program, authorization rules program and authorization rules
+/// (the metadata program ID in place of both if the NFT has no rule set)
+pub struct ProgrammableTransfer<'info> {
+    pub metadata: AccountInfo<'info>,
+    pub edition: AccountInfo<'info>,
+    pub owner_token_record: AccountInfo<'info>,
+    pub destination_token_record: AccountInfo<'info>,
+    pub sysvar_instructions: AccountInfo<'info>,
+    pub metadata_program: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+    pub authorization_rules_program: AccountInfo<'info>,
+    pub authorization_rules: AccountInfo<'info>
+}
+
+impl<'info> ProgrammableTransfer<'info> {
+    pub const ACCOUNTS_LEN: usize = 9;
+
+    /// Reads the accounts of the leg if its NFT is programmable (the leg starts with its metadata
+    /// and the token standard is ProgrammableNonFungible) and returns the remaining accounts after them
+    /// * token and destination are the token accounts the NFT is moved between
+    pub fn split<'a>(
+        remaining_accounts: &'a [AccountInfo<'info>],
+        mint: &Pubkey,
+        token: &Pubkey,
+        destination: &Pubkey
+    ) -> Result<(Option<Self>, &'a [AccountInfo<'info>])> {
+        if !Self::is_programmable(remaining_accounts.first(), mint)? {
+            return Ok((None, remaining_accounts));
+        }
+
+        require_gte!(remaining_accounts.len(), Self::ACCOUNTS_LEN, Errors::AccountNotProvided);
+
+        let (accounts, rest) = remaining_accounts.split_at(Self::ACCOUNTS_LEN);
+        let keys: Vec<Pubkey> = accounts.iter().map(|account| account.key()).collect();
+        Self::check_keys(&keys, mint, token, destination)?;
+
+        let programmable_transfer = Self {
+            metadata: accounts[0].clone(),
+            edition: accounts[1].clone(),
+            owner_token_record: accounts[2].clone(),
+            destination_token_record: accounts[3].clone(),
+            sysvar_instructions: accounts[4].clone(),
+            metadata_program: accounts[5].clone(),
+            associated_token_program: accounts[6].clone(),
+            authorization_rules_program: accounts[7].clone(),
+            authorization_rules: accounts[8].clone()
+        };
+        Ok((Some(programmable_transfer), rest))
+    }
+
+    /// Reads the token standard from the metadata of the mint (if the leg starts with it)
+    fn is_programmable(metadata: Option<&AccountInfo<'info>>, mint: &Pubkey) -> Result<bool> {
+        let metadata = match metadata {
+            Some(metadata) if metadata.key() == metadata_address(mint, &[]) => metadata,
+            _ => {
+                return Ok(false);
+            }
+        };
+
+        let metadata: Account<MetadataAccount> = Account::try_from(metadata)?;
+        Ok(metadata.token_standard == Some(TokenStandard::ProgrammableNonFungible))
+    }
+
+    /// Verifies the keys of the accounts of the leg against the PDAs of the NFT and the program IDs
+    fn check_keys(keys: &[Pubkey], mint: &Pubkey, token: &Pubkey, destination: &Pubkey) -> Result<()> {
+        let expected = [
+            metadata_address(mint, &[]),
+            metadata_address(mint, &[b"edition"]),
+            metadata_address(mint, &[b"token_record", token.as_ref()]),
+            metadata_address(mint, &[b"token_record", destination.as_ref()]),
+            sysvar::instructions::ID,
+            MPL_TOKEN_METADATA_ID,
+            associated_token::ID
+        ];
+        require!(keys[..expected.len()] == expected[..], Errors::InvalidProgrammableAccounts);
+
+        // Without a rule set, the metadata program ID stands in for the rules and their program
+        let (rules_program, rules) = (keys[7], keys[8]);
+        require!(
+            rules_program == MPL_TOKEN_AUTH_RULES_ID || (rules_program == MPL_TOKEN_METADATA_ID && rules == MPL_TOKEN_METADATA_ID),
+            Errors::InvalidProgrammableAccounts
+        );
+        Ok(())
+    }
+
+    /// Transfers the NFT with the metadata program, which thaws the sender and freezes the receiver
+    /// * the authority holds the sender (the party, or the escrow authority out of the escrow)
+    #[allow(clippy::too_many_arguments)]
+    pub fn transfer(
+        &self,
+        token: &AccountInfo<'info>,
+        token_owner: &AccountInfo<'info>,
+        destination: &AccountInfo<'info>,
+        destination_owner: &AccountInfo<'info>,
+        mint: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        token_program: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]]
+    ) -> Result<()> {
+        let mut builder = TransferBuilder::new();
+        builder
+            .token(token.key())
+            .token_owner(token_owner.key())
+            .destination(destination.key())
+            .destination_owner(destination_owner.key())
+            .mint(mint.key())
+            .metadata(self.metadata.key())
+            .edition(self.edition.key())
+            .owner_token_record(self.owner_token_record.key())
+            .destination_token_record(self.destination_token_record.key())
+            .authority(token_owner.key())
+            .payer(payer.key())
+            .system_program(system_program.key())
+            .sysvar_instructions(self.sysvar_instructions.key())
+            .spl_token_program(token_program.key())
+            .spl_ata_program(self.associated_token_program.key());
+
+        if self.authorization_rules_program.key() != MPL_TOKEN_METADATA_ID {
+            builder
+                .authorization_rules_program(self.authorization_rules_program.key())
+                .authorization_rules(self.authorization_rules.key());
+        }
+
+        let instruction = builder
+            .build(TransferArgs::V1 { amount: 1, authorization_data: None })
+            .map_err(|_| Errors::AccountNotProvided)?
+            .instruction();
+
+        invoke_signed(
+            &instruction,
+            &[
+                token.clone(),
+                token_owner.clone(),
+                destination.clone(),
+                destination_owner.clone(),
+                mint.clone(),
+                self.metadata.clone(),
+                self.edition.clone(),
+                self.owner_token_record.clone(),
+                self.destination_token_record.clone(),
+                payer.clone(),
+                system_program.clone(),
+                self.sysvar_instructions.clone(),
+                token_program.clone(),
+                self.associated_token_program.clone(),
+                self.authorization_rules_program.clone(),
+                self.authorization_rules.clone(),
+                self.metadata_program.clone()
+            ],
+            signer_seeds
+        ).map_err(Into::into)
+    }
+}
+
+/// Returns the PDA of the metadata program for the mint with the seeds after the mint
+fn metadata_address(mint: &Pubkey, seeds: &[&[u8]]) -> Pubkey {
+    let mut all_seeds: Vec<&[u8]> = vec![b"metadata", MPL_TOKEN_METADATA_ID.as_ref(), mint.as_ref()];
+    all_seeds.extend_from_slice(seeds);
+
+    Pubkey::find_program_address(&all_seeds, &MPL_TOKEN_METADATA_ID).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leg_keys(mint: &Pubkey, token: &Pubkey, destination: &Pubkey) -> Vec<Pubkey> {
+        vec![
+            metadata_address(mint, &[]),
+            metadata_address(mint, &[b"edition"]),
+            metadata_address(mint, &[b"token_record", token.as_ref()]),
+            metadata_address(mint, &[b"token_record", destination.as_ref()]),
+            sysvar::instructions::ID,
+            MPL_TOKEN_METADATA_ID,
+            associated_token::ID,
+            MPL_TOKEN_METADATA_ID,
+            MPL_TOKEN_METADATA_ID
+        ]
+    }
+
+    #[test]
+    fn accepts_the_accounts_of_the_leg() {
+        let (mint, token, destination) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut keys = leg_keys(&mint, &token, &destination);
+        assert!(ProgrammableTransfer::check_keys(&keys, &mint, &token, &destination).is_ok());
+
+        keys[7] = MPL_TOKEN_AUTH_RULES_ID;
+        keys[8] = Pubkey::new_unique();
+        assert!(ProgrammableTransfer::check_keys(&keys, &mint, &token, &destination).is_ok());
+    }
+
+    #[test]
+    fn rejects_a_swapped_or_foreign_account() {
+        let (mint, token, destination) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let keys = leg_keys(&mint, &token, &destination);
+
+        for index in 0..ProgrammableTransfer::ACCOUNTS_LEN - 1 {
+            let mut wrong_keys = keys.clone();
+            wrong_keys[index] = Pubkey::new_unique();
+            assert!(ProgrammableTransfer::check_keys(&wrong_keys, &mint, &token, &destination).is_err());
+        }
+
+        // The token records of the sender and the receiver swapped
+        let mut wrong_keys = keys.clone();
+        wrong_keys.swap(2, 3);
+        assert!(ProgrammableTransfer::check_keys(&wrong_keys, &mint, &token, &destination).is_err());
+
+        // Rules without their program
+        let mut wrong_keys = keys;
+        wrong_keys[8] = Pubkey::new_unique();
+        assert!(ProgrammableTransfer::check_keys(&wrong_keys, &mint, &token, &destination).is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::AccountMeta,
+    program::invoke_signed
+};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        BaseStateWithExtensions,
+        StateWithExtensions,
+        transfer_fee::{TransferFeeAmount, instruction::harvest_withheld_tokens_to_mint}
+    },
+    state::Account as SplTokenAccount
+};
+
+/// Transfers the tokens with `transfer_checked` (required by the Token-2022 mints).
+/// The remaining accounts are forwarded for the transfer hook of the mint, if any
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked_with_hook<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]]
+) -> Result<()> {
+    let mut instruction = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        from.key,
+        mint.key,
+        to.key,
+        authority.key,
+        &[],
+        amount,
+        decimals
+    )?;
+
+    let mut account_infos = vec![
+        from.clone(),
+        mint.clone(),
+        to.clone(),
+        authority.clone()
+    ];
+
+    // The extra accounts of the hook (and the hook program) are read by Token-2022 from the instruction
+    for account in remaining_accounts {
+        instruction.accounts.push(if account.is_writable {
+            AccountMeta::new(account.key(), false)
+        } else {
+            AccountMeta::new_readonly(account.key(), false)
+        });
+        account_infos.push(account.clone());
+    }
+
+    invoke_signed(&instruction, &account_infos, signer_seeds).map_err(Into::into)
+}
+
+/// Moves the transfer fees withheld in the token account to its mint,
+/// a Token-2022 account can't be closed while it withholds any fee
+pub fn harvest_withheld_fees<'info>(
+    token_program: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>
+) -> Result<()> {
+    let withheld_amount = {
+        let data = token_account.try_borrow_data()?;
+        let state = StateWithExtensions::<SplTokenAccount>::unpack(&data)?;
+
+        match state.get_extension::<TransferFeeAmount>() {
+            Ok(fee_amount) => u64::from(fee_amount.withheld_amount),
+            Err(_) => 0
+        }
+    };
+
+    if withheld_amount == 0 {
+        return Ok(());
+    }
+
+    let instruction = harvest_withheld_tokens_to_mint(
+        token_program.key,
+        mint.key,
+        &[token_account.key]
+    )?;
+
+    invoke_signed(
+        &instruction,
+        &[mint.clone(), token_account.clone()],
+        &[]
+    ).map_err(Into::into)
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use crate::Errors;
+
+/// Fails if any account is passed more than once
+/// (the batches read each account on its own, a repeated one would be counted twice)
+pub fn require_unique_keys<'a>(keys: impl IntoIterator<Item = &'a Pubkey>) -> Result<()> {
+    let mut keys: Vec<&Pubkey> = keys.into_iter().collect();
+    keys.sort_unstable();
+
+    require!(keys.windows(2).all(|pair| pair[0] != pair[1]), Errors::DuplicateAccount);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_distinct_keys() {
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+
+        assert!(require_unique_keys(keys.iter()).is_ok());
+    }
+
+    #[test]
+    fn rejects_the_same_stake_twice() {
+        let stake = Pubkey::new_unique();
+        let keys = [stake, Pubkey::new_unique(), stake];
+
+        assert_eq!(
+            require_unique_keys(keys.iter()).unwrap_err(),
+            Errors::DuplicateAccount.into()
+        );
+    }
+}
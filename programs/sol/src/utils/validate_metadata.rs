@@ -9,6 +9,33 @@ pub fn validate_metadata_account(
     metadata: &AccountInfo, 
     verified_key: &Pubkey
 ) -> Result<u64> {
+    let metadata_account = verify_metadata_account(mint, metadata, verified_key)?;
+
+    // Extracts the ID of the NFT from its name in the metadata
+    let nft_name = &metadata_account.data.name;
+    let nft_name_trun = nft_name.replace("\x00","");
+    let bytes = nft_name_trun.as_bytes();
+
+    let mut nft_num_string = "";
+
+    for (i,&item) in bytes.iter().enumerate() {
+        if item == b'#' {
+            nft_num_string = &nft_name_trun[i+1..nft_name_trun.len()];
+            break;
+        }
+    }
+
+    let nft_num: u64 = nft_num_string.parse().unwrap();
+
+    Ok(nft_num)
+}
+
+/// Verifies that the metadata belongs to the mint and to the verified collection
+pub fn verify_metadata_account<'info>(
+    mint: &Pubkey, 
+    metadata: &AccountInfo<'info>, 
+    verified_key: &Pubkey
+) -> Result<Account<'info, MetadataAccount>> {
     
     // Verify whether the metadata account is initialized
     require_eq!(metadata.data_is_empty(), false, Errors::AccountNotInitialized);
@@ -37,21 +64,5 @@ pub fn validate_metadata_account(
         return Err(Errors::CollectionNotSet.into());
     }
 
-    // Extracts the ID of the NFT from its name in the metadata
-    let nft_name = &metadata_account.data.name;
-    let nft_name_trun = nft_name.replace("\x00","");
-    let bytes = nft_name_trun.as_bytes();
-
-    let mut nft_num_string = "";
-
-    for (i,&item) in bytes.iter().enumerate() {
-        if item == b'#' {
-            nft_num_string = &nft_name_trun[i+1..nft_name_trun.len()];
-            break;
-        }
-    }
-
-    let nft_num: u64 = nft_num_string.parse().unwrap();
-
-    Ok(nft_num)
-}
\ No newline at end of file
+    Ok(metadata_account)
+}
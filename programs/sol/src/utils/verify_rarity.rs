@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use crate::Errors;
+
+/// The rarity multiplier of an NFT earning the plain emission (in percent)
+pub const BASE_MULTIPLIER: u16 = 100;
+
+/// Verifies the (mint, multiplier) leaf against the merkle root of the rarity table
+/// * leaf = keccak(0x00 || mint || multiplier as u16 LE)
+/// * node = keccak(0x01 || lower hash || higher hash) (the pair is sorted, no position is needed)
+pub fn verify_rarity(
+    root: &[u8; 32],
+    mint: &Pubkey,
+    multiplier: u16,
+    proof: &[[u8; 32]]
+) -> Result<()> {
+    let mut hash = keccak::hashv(&[&[0u8], mint.as_ref(), &multiplier.to_le_bytes()]).0;
+
+    for sibling in proof {
+        hash = if hash <= *sibling {
+            keccak::hashv(&[&[1u8], &hash, sibling]).0
+        } else {
+            keccak::hashv(&[&[1u8], sibling, &hash]).0
+        };
+    }
+
+    require!(hash == *root, Errors::InvalidRarityProof);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(mint: &Pubkey, multiplier: u16) -> [u8; 32] {
+        keccak::hashv(&[&[0u8], mint.as_ref(), &multiplier.to_le_bytes()]).0
+    }
+
+    fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let (lower, higher) = if a <= b { (a, b) } else { (b, a) };
+        keccak::hashv(&[&[1u8], lower, higher]).0
+    }
+
+    type Proof = Vec<[u8; 32]>;
+
+    /// A rarity table of four NFTs, with the root and the proof of each leaf
+    fn rarity_table() -> (Vec<(Pubkey, u16)>, [u8; 32], Vec<Proof>) {
+        let entries: Vec<(Pubkey, u16)> = [100, 150, 200, 300]
+            .iter()
+            .map(|multiplier| (Pubkey::new_unique(), *multiplier))
+            .collect();
+        let leaves: Vec<[u8; 32]> = entries.iter().map(|(mint, multiplier)| leaf(mint, *multiplier)).collect();
+
+        let left = node(&leaves[0], &leaves[1]);
+        let right = node(&leaves[2], &leaves[3]);
+        let root = node(&left, &right);
+
+        let proofs = vec![
+            vec![leaves[1], right],
+            vec![leaves[0], right],
+            vec![leaves[3], left],
+            vec![leaves[2], left]
+        ];
+        (entries, root, proofs)
+    }
+
+    #[test]
+    fn accepts_every_leaf_of_the_table() {
+        let (entries, root, proofs) = rarity_table();
+
+        for ((mint, multiplier), proof) in entries.iter().zip(&proofs) {
+            assert!(verify_rarity(&root, mint, *multiplier, proof).is_ok());
+        }
+    }
+
+    #[test]
+    fn accepts_a_single_leaf_table() {
+        let mint = Pubkey::new_unique();
+        let root = leaf(&mint, 250);
+
+        assert!(verify_rarity(&root, &mint, 250, &[]).is_ok());
+        assert!(verify_rarity(&root, &mint, 100, &[]).is_err());
+    }
+
+    #[test]
+    fn rejects_a_wrong_multiplier_mint_or_proof() {
+        let (entries, root, proofs) = rarity_table();
+        let (mint, multiplier) = entries[1];
+
+        assert!(verify_rarity(&root, &mint, 300, &proofs[1]).is_err());
+        assert!(verify_rarity(&root, &Pubkey::new_unique(), multiplier, &proofs[1]).is_err());
+        assert!(verify_rarity(&root, &mint, multiplier, &proofs[0]).is_err());
+        assert!(verify_rarity(&root, &mint, multiplier, &proofs[1][..1]).is_err());
+        assert!(verify_rarity(&[0; 32], &mint, multiplier, &proofs[1]).is_err());
+    }
+}